@@ -0,0 +1,123 @@
+//! Decimal-safe parsing of payment amounts
+//!
+//! Amounts are converted to integer minor units (cents) without floating
+//! point, so `19.99` is 1999 and never 1998.
+
+use serde_json::Value;
+
+/// Parse a plain decimal like `19.99` into minor units with `exponent`
+/// decimal places. More decimal places than the currency has is an error.
+pub fn parse_decimal(text: &str, exponent: u32) -> Result<i64, String> {
+    let invalid = || format!("invalid amount {}", text);
+
+    let (integer, fraction) = match text.split_once('.') {
+        Some((_, "")) => return Err(invalid()),
+        Some((integer, fraction)) => (integer, fraction),
+        None => (text, ""),
+    };
+
+    if integer.is_empty()
+        || !integer.bytes().all(|b| b.is_ascii_digit())
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > exponent as usize {
+        return Err(format!(
+            "amount {} has more than {} decimal places",
+            text, exponent
+        ));
+    }
+
+    let scale = 10i64.pow(exponent);
+    let fraction_minor = if fraction.is_empty() {
+        0
+    } else {
+        let padding = 10i64.pow(exponent - fraction.len() as u32);
+        fraction.parse::<i64>().map_err(|_| invalid())? * padding
+    };
+
+    integer
+        .parse::<i64>()
+        .ok()
+        .and_then(|units| units.checked_mul(scale))
+        .and_then(|minor| minor.checked_add(fraction_minor))
+        .ok_or_else(|| format!("amount {} is out of range", text))
+}
+
+/// Read a decimal amount from a JSON string or number into minor units.
+///
+/// serde_json is built with `arbitrary_precision`, so a number's `Display`
+/// is its text as the bank wrote it, never an `f64` rounding of it.
+pub fn json_decimal(value: &Value, exponent: u32) -> Result<i64, String> {
+    match value {
+        Value::String(text) => parse_decimal(text.trim(), exponent),
+        Value::Number(number) => parse_decimal(&number.to_string(), exponent),
+        _ => Err(format!("amount {} is not a number", value)),
+    }
+}
+
+/// Read an amount already in minor units (e.g. `amount_cents`)
+pub fn json_minor_units(value: &Value) -> Result<i64, String> {
+    json_decimal(value, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_decimal() {
+        assert_eq!(parse_decimal("19.99", 2), Ok(1999));
+        assert_eq!(parse_decimal("0.29", 2), Ok(29));
+        assert_eq!(parse_decimal("100", 2), Ok(10000));
+        assert_eq!(parse_decimal("100.5", 2), Ok(10050));
+        assert_eq!(parse_decimal("100.500", 2), Ok(10050));
+        assert_eq!(parse_decimal("1999", 0), Ok(1999));
+
+        assert!(parse_decimal("19.999", 2).is_err());
+        assert!(parse_decimal("19.9", 0).is_err());
+        for invalid in [
+            "",
+            ".5",
+            "5.",
+            "-5",
+            "1e3",
+            "1,000.00",
+            " 5",
+            "99999999999999999999",
+        ] {
+            assert!(
+                parse_decimal(invalid, 2).is_err(),
+                "{} should be rejected",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_json_amounts() {
+        // Float amounts that truncate when multiplied by 100
+        assert_eq!(json_decimal(&json!(19.99), 2), Ok(1999));
+        assert_eq!(json_decimal(&json!(0.29), 2), Ok(29));
+        assert_eq!(json_decimal(&json!(1234567.89), 2), Ok(123456789));
+        assert_eq!(json_decimal(&json!("19.99"), 2), Ok(1999));
+        assert_eq!(json_decimal(&json!(100), 2), Ok(10000));
+
+        // Amounts with more significant digits than an f64 holds are exact
+        let value: Value = serde_json::from_str("90071992547409.93").unwrap();
+        assert_eq!(json_decimal(&value, 2), Ok(9007199254740993));
+        let value: Value = serde_json::from_str("9007199254740993").unwrap();
+        assert_eq!(json_minor_units(&value), Ok(9007199254740993));
+        let value: Value = serde_json::from_str("0.100000000000000000001").unwrap();
+        assert!(json_decimal(&value, 2).is_err());
+
+        assert_eq!(json_minor_units(&json!(1999)), Ok(1999));
+        assert_eq!(json_minor_units(&json!("1999")), Ok(1999));
+        assert!(json_minor_units(&json!(19.99)).is_err());
+        assert!(json_decimal(&json!(null), 2).is_err());
+    }
+}
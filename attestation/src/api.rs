@@ -1,21 +1,32 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
 use axum::{
-    extract::State,
+    extract::{Path, State},
     http::{HeaderMap, StatusCode},
-    response::IntoResponse,
-    Json,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
 };
+use alloy_primitives::keccak256;
 use serde::Serialize;
-use tracing::{info, warn};
+use tower_http::cors::CorsLayer;
+use tower_http::trace::TraceLayer;
+use tracing::{error, info, warn};
 
-use crate::attestation::{create_attestation, AttestationRequest, AttestationResponse};
+use crate::attestation::{create_attestation, verify_request, AttestationRequest, AttestationResponse};
 use crate::audit::{current_timestamp, AuditLogEntry, AuditLogger, AuditResult};
 use crate::auth::SolverAuth;
-use crate::chain::ChainClient;
+use crate::chain::{ChainClient, OnChainIntent};
+use crate::cluster::{CosignRequest, WitnessCluster, CLUSTER_KEY_HEADER};
 use crate::config::Config;
+use crate::eip712::SchemaVersion;
 use crate::error::AttestationError;
+use crate::providers::ProviderRegistry;
+use crate::self_check::{run_self_check, SelfCheckReport};
+use crate::witness::WitnessKeyInfo;
+use crate::store::AttestationStore;
+use crate::verification::{PresentationVerifier, TlsnVerifier, VerifiedPayment};
 
 /// Application state shared across handlers
 pub struct AppState {
@@ -23,13 +34,75 @@ pub struct AppState {
     pub auth: SolverAuth,
     pub chain: Option<ChainClient>,
     pub audit: AuditLogger,
+    pub providers: ProviderRegistry,
+    /// Checks presentations; `TlsnVerifier` outside of tests
+    pub verifier: Box<dyn PresentationVerifier>,
+    pub store: AttestationStore,
+    pub cluster: WitnessCluster,
+    /// Latest verifier self-check, if one ran
+    pub self_check: Mutex<Option<SelfCheckReport>>,
 }
 
+/// The service's HTTP routes
+pub fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/api/v1/attest", post(attest))
+        .route("/api/v1/cosign", post(cosign))
+        .route("/api/v1/attestations/{intent_hash}", get(get_attestation))
+        .route("/api/v1/health", get(health))
+        .route("/api/v1/reference/{intent_hash}", get(payment_reference))
+        .layer(CorsLayer::permissive())
+        .layer(TraceLayer::new_for_http())
+        .with_state(state)
+}
+
+/// Age after which the verifier self-check is re-run
+pub const SELF_CHECK_INTERVAL_SECS: u64 = 60;
+
 impl AppState {
     pub fn new(config: Config) -> anyhow::Result<Self> {
         let auth = SolverAuth::from_env();
         let chain = ChainClient::from_env();
         let audit = AuditLogger::new();
+        let providers =
+            ProviderRegistry::from_policy(&config.server_policy).map_err(anyhow::Error::msg)?;
+        let store = AttestationStore::open(&config.database_path).map_err(|e| {
+            anyhow::anyhow!("Cannot open {}: {}", config.database_path.display(), e)
+        })?;
+        let cluster = WitnessCluster::from_env()?;
+
+        info!("Environment: {}", config.environment);
+        info!("Allowed servers: {}", providers.describe().join(", "));
+        info!("Attestation store: {}", config.database_path.display());
+        for key in config.witness_keys.describe(current_timestamp()) {
+            info!(
+                "Witness key {}: {} ({}, {:?})",
+                key.id, key.address, key.signer, key.status
+            );
+        }
+        if cluster.is_enabled() {
+            info!(
+                "Witness cluster: {} of {} signatures required, peers {}",
+                cluster.min_witnesses(),
+                cluster.peers().len() + 1,
+                cluster.peers().join(", ")
+            );
+        }
+
+        if config.environment.requires_safeguards() {
+            if !auth.is_enabled() {
+                anyhow::bail!(
+                    "Solver authentication must be enabled in {} - set SOLVER_API_KEYS",
+                    config.environment
+                );
+            }
+            if chain.is_none() {
+                anyhow::bail!(
+                    "On-chain validation must be enabled in {} - set RPC_URL and OFFRAMP_CONTRACT",
+                    config.environment
+                );
+            }
+        }
 
         if auth.is_enabled() {
             info!("Solver authentication enabled ({} solvers)", auth.solver_count());
@@ -37,10 +110,21 @@ impl AppState {
             warn!("Solver authentication DISABLED - set SOLVER_API_KEYS to enable");
         }
 
-        if let Some(ref c) = chain {
+        if chain.is_some() {
             info!("On-chain intent validation enabled");
             info!("  RPC URL: {}", std::env::var("RPC_URL").unwrap_or_default());
             info!("  Contract: {}", std::env::var("OFFRAMP_CONTRACT").unwrap_or_default());
+            match chain.as_ref().and_then(ChainClient::verifier_contract) {
+                Some(verifier) => {
+                    info!("  Verifier: {} (nullifier checks enabled)", verifier);
+                    // Witness keys sign only once the verifier trusts them
+                    config.witness_keys.require_authorization();
+                }
+                None => warn!("  Verifier contract not set - nullifiers are not checked before signing"),
+            }
+            if chain.as_ref().is_some_and(ChainClient::simulates_fulfillment) {
+                info!("  Fulfillment simulation enabled");
+            }
         } else {
             warn!("On-chain validation DISABLED - set RPC_URL and OFFRAMP_CONTRACT to enable");
         }
@@ -50,33 +134,227 @@ impl AppState {
             auth,
             chain,
             audit,
+            providers,
+            verifier: Box::new(TlsnVerifier),
+            store,
+            cluster,
+            self_check: Mutex::new(None),
         })
     }
+
+    /// Check the deployed verifier trusts the witness keys and hashes
+    /// attestations like the service does. A domain mismatch, or no active
+    /// key the verifier trusts, is fatal in environments requiring safeguards;
+    /// anything else only degrades health.
+    pub async fn startup_self_check(&self) -> anyhow::Result<()> {
+        let Some(report) = self.refresh_self_check().await else {
+            return Ok(());
+        };
+
+        if report.passed() {
+            info!("Verifier self-check passed ({})", report.verifier);
+            return Ok(());
+        }
+
+        for check in &report.checks {
+            if let Some(detail) = &check.detail {
+                warn!(check = check.name, status = ?check.status, "Verifier self-check: {}", detail);
+            }
+        }
+
+        let message = if !report.configuration_matches() {
+            let mismatches: Vec<_> = report
+                .mismatches()
+                .filter(|c| c.witness.is_none())
+                .map(|c| c.name)
+                .collect();
+            format!(
+                "Verifier {} does not match this witness configuration ({}) - attestations would not verify on-chain",
+                report.verifier,
+                mismatches.join(", ")
+            )
+        } else if self.config.witness_address().is_none() {
+            format!(
+                "Verifier {} trusts no active witness key - attestations cannot be signed",
+                report.verifier
+            )
+        } else {
+            return Ok(());
+        };
+
+        if self.config.environment.requires_safeguards() {
+            anyhow::bail!(message);
+        }
+        error!("{}", message);
+
+        Ok(())
+    }
+
+    /// Latest verifier self-check, re-run when older than
+    /// `SELF_CHECK_INTERVAL_SECS`
+    pub async fn current_self_check(&self) -> Option<SelfCheckReport> {
+        let cached = self.self_check.lock().unwrap().clone();
+        match cached {
+            Some(report) if current_timestamp() < report.checked_at + SELF_CHECK_INTERVAL_SECS => {
+                Some(report)
+            }
+            _ => self.refresh_self_check().await,
+        }
+    }
+
+    /// Run the verifier self-check, keep its report and apply the witness
+    /// authorizations it found, which may rotate the primary key
+    async fn refresh_self_check(&self) -> Option<SelfCheckReport> {
+        let chain = self.chain.as_ref()?;
+        let domain = self.config.domain(SchemaVersion::V1);
+        let keys = &self.config.witness_keys;
+        let witnesses = keys.unretired_addresses(current_timestamp());
+
+        let report =
+            run_self_check(chain, &witnesses, &domain, self.cluster.min_witnesses()).await?;
+
+        let before = self.config.witness_address();
+        keys.record_authorization(&report.authorized);
+        let after = self.config.witness_address();
+        if after != before {
+            match after {
+                Some(address) => info!("Witness key rotated: now signing as 0x{}", hex::encode(address)),
+                None => warn!("No active witness key is authorized - signing is suspended"),
+            }
+        }
+
+        *self.self_check.lock().unwrap() = Some(report.clone());
+        Some(report)
+    }
+
+    /// Run `f` against the attestation store on the blocking thread pool,
+    /// since SQLite calls block and may wait for the connection lock
+    pub async fn with_store<T, F>(self: &Arc<Self>, f: F) -> Result<T, AttestationError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&AttestationStore) -> Result<T, AttestationError> + Send + 'static,
+    {
+        let state = Arc::clone(self);
+        tokio::task::spawn_blocking(move || f(&state.store))
+            .await
+            .map_err(|e| AttestationError::Internal(format!("Store task failed: {}", e)))?
+    }
 }
 
 /// Health check response
 #[derive(Serialize)]
 pub struct HealthResponse {
     pub status: String,
+    pub environment: String,
+    /// Address of the key currently signing (empty if none can sign)
     pub witness_address: String,
+    pub witness_keys: Vec<WitnessKeyInfo>,
+    /// Witness signatures collected per attestation
+    pub min_witnesses: usize,
+    /// Peer witnesses co-signing in cluster mode
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub witness_peers: Vec<String>,
     pub chain_id: u64,
     pub auth_enabled: bool,
     pub chain_validation_enabled: bool,
+    /// Verifier self-check, when a verifier contract is configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub self_check: Option<SelfCheckReport>,
 }
 
 /// Health check endpoint
 pub async fn health(State(state): State<Arc<AppState>>) -> Json<HealthResponse> {
-    let witness_address = format!("0x{}", hex::encode(state.config.witness_address()));
+    let self_check = state.current_self_check().await;
+    let witness_address = state
+        .config
+        .witness_address()
+        .map(|address| format!("0x{}", hex::encode(address)))
+        .unwrap_or_default();
+
+    // Signatures the verifier can't be shown to accept make the service degraded
+    let status = match &self_check {
+        _ if witness_address.is_empty() => "degraded",
+        Some(report) if !report.passed() => "degraded",
+        _ => "ok",
+    };
 
     Json(HealthResponse {
-        status: "ok".to_string(),
+        status: status.to_string(),
+        environment: state.config.environment.to_string(),
         witness_address,
+        witness_keys: state.config.witness_keys.describe(current_timestamp()),
+        min_witnesses: state.cluster.min_witnesses(),
+        witness_peers: state.cluster.peers().to_vec(),
         chain_id: state.config.chain_id,
         auth_enabled: state.auth.is_enabled(),
         chain_validation_enabled: state.chain.is_some(),
+        self_check,
     })
 }
 
+/// Payment reference response
+#[derive(Serialize)]
+pub struct PaymentReferenceResponse {
+    pub intent_hash: String,
+    pub reference: String,
+}
+
+/// Payment reference a solver must put on the transfer for an intent
+pub async fn payment_reference(
+    Path(intent_hash): Path<String>,
+) -> Result<Json<PaymentReferenceResponse>, Response> {
+    let intent_bytes = decode_bytes32(&intent_hash).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(AuthErrorResponse {
+                success: false,
+                error: format!("Invalid intent hash: {}", e),
+            }),
+        )
+            .into_response()
+    })?;
+
+    Ok(Json(PaymentReferenceResponse {
+        intent_hash: format!("0x{}", hex::encode(intent_bytes)),
+        reference: crate::reference::payment_reference(&intent_bytes),
+    }))
+}
+
+/// Stored attestation endpoint: returns the attestation signed for an intent
+/// to the solver it was signed for
+pub async fn get_attestation(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(intent_hash): Path<String>,
+) -> Result<Json<AttestationResponse>, Response> {
+    let solver_address = authenticate_solver(&state, &headers).map_err(IntoResponse::into_response)?;
+
+    let intent_bytes = decode_bytes32(&intent_hash).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(AuthErrorResponse {
+                success: false,
+                error: format!("Invalid intent hash: {}", e),
+            }),
+        )
+            .into_response()
+    })?;
+
+    match state.with_store(move |store| store.attestation(&intent_bytes)).await {
+        // Another solver's attestation is reported as missing
+        Ok(Some(stored)) if stored.solver_address == solver_address => Ok(Json(stored.response)),
+        Ok(_) => Err((
+            StatusCode::NOT_FOUND,
+            Json(AuthErrorResponse {
+                success: false,
+                error: format!("No attestation for intent {}", intent_hash),
+            }),
+        )
+            .into_response()),
+        Err(e) => Err(e.into_response()),
+    }
+}
+
 /// Rate limit error response
 #[derive(Serialize)]
 pub struct RateLimitResponse {
@@ -101,46 +379,8 @@ pub async fn attest(
     let start_time = Instant::now();
     let intent_hash = request.intent_hash.clone();
 
-    // Extract API key from header
-    let api_key = headers
-        .get("x-solver-api-key")
-        .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string());
-
     // Authenticate solver (if auth is enabled)
-    let solver_address = if state.auth.is_enabled() {
-        let key = match api_key {
-            Some(k) => k,
-            None => {
-                return Err((
-                    StatusCode::UNAUTHORIZED,
-                    Json(AuthErrorResponse {
-                        success: false,
-                        error: "Missing X-Solver-API-Key header".to_string(),
-                    }),
-                )
-                    .into_response());
-            }
-        };
-
-        match state.auth.validate_api_key(&key) {
-            Some(addr) => addr,
-            None => {
-                warn!(api_key = %key, "Invalid API key");
-                return Err((
-                    StatusCode::UNAUTHORIZED,
-                    Json(AuthErrorResponse {
-                        success: false,
-                        error: "Invalid API key".to_string(),
-                    }),
-                )
-                    .into_response());
-            }
-        }
-    } else {
-        // Auth disabled, use placeholder
-        "0x0000000000000000000000000000000000000000".to_string()
-    };
+    let solver_address = authenticate_solver(&state, &headers).map_err(IntoResponse::into_response)?;
 
     // Check rate limit
     if let Err(retry_after) = state.auth.check_rate_limit(&solver_address) {
@@ -167,78 +407,81 @@ pub async fn attest(
         "Processing attestation request"
     );
 
-    // Validate intent on-chain (if enabled)
-    if let Some(ref chain) = state.chain {
-        let intent_bytes = match decode_bytes32(&request.intent_hash) {
-            Ok(b) => b,
-            Err(e) => {
-                let duration_ms = start_time.elapsed().as_millis() as u64;
-                state.audit.log(&AuditLogEntry {
-                    timestamp: current_timestamp(),
-                    solver_address: solver_address.clone(),
-                    intent_hash: intent_hash.clone(),
-                    payment_id: None,
-                    amount_cents: request.expected_amount_cents,
-                    result: AuditResult::Rejected {
-                        reason: format!("Invalid intent hash: {}", e),
-                    },
-                    request_ip: None,
-                    duration_ms,
-                });
-
-                return Err((
-                    StatusCode::BAD_REQUEST,
-                    Json(AuthErrorResponse {
-                        success: false,
-                        error: format!("Invalid intent hash: {}", e),
-                    }),
-                )
-                    .into_response());
+    // An identical resubmission (same solver, presentation and schema) gets
+    // the attestation already signed for it, without verifying the
+    // presentation again
+    let presentation_hash = keccak256(request.presentation.as_bytes()).0;
+    if let Ok(intent_bytes) = decode_bytes32(&request.intent_hash) {
+        let stored = state.with_store(move |store| store.attestation(&intent_bytes)).await;
+        let stored = stored.and_then(|stored| match stored {
+            Some(stored)
+                if stored.solver_address == solver_address
+                    && stored.presentation_hash == presentation_hash
+                    && stored.response.schema_version == request.schema_version =>
+            {
+                Ok(Some(stored.response))
             }
-        };
+            Some(_) => Err(AttestationError::IntentAlreadyAttested(intent_hash.clone())),
+            None => Ok(None),
+        });
 
-        if let Err(e) = crate::chain::validate_intent(
-            chain,
-            intent_bytes,
-            &solver_address,
-            request.expected_amount_cents,
-        )
-        .await
-        {
-            let duration_ms = start_time.elapsed().as_millis() as u64;
-            state.audit.log(&AuditLogEntry {
-                timestamp: current_timestamp(),
-                solver_address: solver_address.clone(),
-                intent_hash: intent_hash.clone(),
-                payment_id: None,
-                amount_cents: request.expected_amount_cents,
-                result: AuditResult::Rejected {
-                    reason: e.clone(),
-                },
-                request_ip: None,
-                duration_ms,
-            });
-
-            warn!(
-                intent_hash = %request.intent_hash,
-                solver = %solver_address,
-                error = %e,
-                "Intent validation failed"
-            );
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(AuthErrorResponse {
-                    success: false,
-                    error: e,
-                }),
-            )
-                .into_response());
+        match stored {
+            Ok(Some(response)) => {
+                info!(
+                    intent_hash = %request.intent_hash,
+                    solver = %solver_address,
+                    "Returning stored attestation for resubmitted presentation"
+                );
+                return Ok(Json(response));
+            }
+            Ok(None) => {}
+            Err(e) => return Err(attestation_failed(&state, &request, &solver_address, start_time, e)),
         }
     }
 
-    // Create attestation
-    match create_attestation(&request, &state.config) {
+    let (verified, intent, intent_bytes) =
+        verify_and_claim(&state, &request, &solver_address, start_time).await?;
+
+    // Create attestation, co-signed by the peer witnesses in cluster mode
+    let result = match create_attestation(&request, verified, intent.as_ref(), &state.config).await {
+        Ok(response) if state.cluster.is_enabled() => state
+            .cluster
+            .cosign(&request, &solver_address, &response)
+            .await
+            .map(|signatures| AttestationResponse { signatures, ..response }),
+        result => result,
+    };
+
+    match result {
         Ok(response) => {
+            // Withhold a signature whose fulfillment would revert on-chain
+            // (OffRampV3 only takes schema v1 attestations)
+            let simulate = response.schema_version == SchemaVersion::V1;
+            if let Some(chain) = state.chain.as_ref().filter(|c| simulate && c.simulates_fulfillment()) {
+                if let Err(e) =
+                    crate::chain::check_fulfillment(chain, &solver_address, intent_bytes, &response).await
+                {
+                    return Err(attestation_failed(&state, &request, &solver_address, start_time, e));
+                }
+            }
+
+            // Keep it so the solver can fetch it again or resubmit idempotently
+            let (solver, stored) = (solver_address.clone(), response.clone());
+            let saved = state
+                .with_store(move |store| {
+                    store.save_attestation(
+                        &intent_bytes,
+                        &solver,
+                        &presentation_hash,
+                        &stored,
+                        current_timestamp(),
+                    )
+                })
+                .await;
+            if let Err(e) = saved {
+                warn!(intent_hash = %request.intent_hash, error = %e, "Failed to store attestation");
+            }
+
             let duration_ms = start_time.elapsed().as_millis() as u64;
             state.audit.log(&AuditLogEntry {
                 timestamp: current_timestamp(),
@@ -259,29 +502,290 @@ pub async fn attest(
             );
             Ok(Json(response))
         }
-        Err(e) => {
+        Err(e) => Err(attestation_failed(&state, &request, &solver_address, start_time, e)),
+    }
+}
+
+/// Co-sign endpoint for peer witnesses: verifies the request the primary
+/// forwarded on its own and signs it with this instance's witness key
+pub async fn cosign(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(cosign): Json<CosignRequest>,
+) -> Result<Json<AttestationResponse>, Response> {
+    let start_time = Instant::now();
+
+    let key = headers.get(CLUSTER_KEY_HEADER).and_then(|v| v.to_str().ok());
+    if !state.cluster.accepts(key) {
+        warn!("Co-sign request without a valid cluster key");
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(AuthErrorResponse {
+                success: false,
+                error: "Invalid X-Cluster-Key header".to_string(),
+            }),
+        )
+            .into_response());
+    }
+
+    let CosignRequest { request, solver_address } = cosign;
+    info!(
+        intent_hash = %request.intent_hash,
+        solver = %solver_address,
+        "Processing co-sign request"
+    );
+
+    let (verified, intent, _) =
+        verify_and_claim(&state, &request, &solver_address, start_time).await?;
+
+    match create_attestation(&request, verified, intent.as_ref(), &state.config).await {
+        Ok(response) => {
             let duration_ms = start_time.elapsed().as_millis() as u64;
             state.audit.log(&AuditLogEntry {
                 timestamp: current_timestamp(),
                 solver_address: solver_address.clone(),
-                intent_hash: intent_hash.clone(),
-                payment_id: None,
-                amount_cents: request.expected_amount_cents,
-                result: AuditResult::Error {
-                    message: e.to_string(),
-                },
+                intent_hash: request.intent_hash.clone(),
+                payment_id: response.payment.transaction_id.clone(),
+                amount_cents: response.payment.amount_cents,
+                result: AuditResult::Success,
                 request_ip: None,
                 duration_ms,
             });
 
-            warn!(
+            info!(
                 intent_hash = %request.intent_hash,
-                error = %e,
-                "Attestation request failed"
+                duration_ms = %duration_ms,
+                "Attestation co-signed"
             );
-            Err(e.into_response())
+            Ok(Json(response))
+        }
+        Err(e) => Err(attestation_failed(&state, &request, &solver_address, start_time, e)),
+    }
+}
+
+/// Check the intent on-chain, verify the presentation against it and bind
+/// the payment to the intent, as the primary and every co-signing peer do
+/// before signing
+async fn verify_and_claim(
+    state: &Arc<AppState>,
+    request: &AttestationRequest,
+    solver_address: &str,
+    start_time: Instant,
+) -> Result<(VerifiedPayment, Option<OnChainIntent>, [u8; 32]), Response> {
+    let intent_hash = request.intent_hash.clone();
+
+    // Decode intent hash up front so malformed requests are rejected before
+    // doing any expensive verification
+    let intent_bytes = if state.chain.is_some() {
+        match decode_bytes32(&request.intent_hash) {
+            Ok(b) => Some(b),
+            Err(e) => {
+                let duration_ms = start_time.elapsed().as_millis() as u64;
+                state.audit.log(&AuditLogEntry {
+                    timestamp: current_timestamp(),
+                    solver_address: solver_address.to_string(),
+                    intent_hash: intent_hash.clone(),
+                    payment_id: None,
+                    amount_cents: request.expected_amount_cents,
+                    result: AuditResult::Rejected {
+                        reason: format!("Invalid intent hash: {}", e),
+                    },
+                    request_ip: None,
+                    duration_ms,
+                });
+
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(AuthErrorResponse {
+                        success: false,
+                        error: format!("Invalid intent hash: {}", e),
+                    }),
+                )
+                    .into_response());
+            }
+        }
+    } else {
+        None
+    };
+
+    // Reject an intent already fulfilled on-chain before verifying anything
+    if let (Some(chain), Some(intent_bytes)) = (&state.chain, intent_bytes) {
+        if let Err(e) = crate::chain::check_intent_unfulfilled(chain, intent_bytes).await {
+            return Err(attestation_failed(state, request, solver_address, start_time, e));
+        }
+    }
+
+    // A payment ID stated in the request is checked before the presentation,
+    // which verify_request then requires to prove that same ID
+    if let (Some(chain), Some(payment_id)) = (&state.chain, request.payment_id.as_deref()) {
+        if let Err(e) = crate::chain::check_payment_unused(chain, payment_id).await {
+            return Err(attestation_failed(state, request, solver_address, start_time, e));
+        }
+    }
+
+    // Verify the TLSNotary presentation
+    let verified = match verify_request(
+        request,
+        &state.config,
+        state.verifier.as_ref(),
+        &state.providers,
+        state.chain.is_some(),
+    ) {
+        Ok(v) => v,
+        Err(e) => return Err(attestation_failed(state, request, solver_address, start_time, e)),
+    };
+
+    // A payment ID consumed on-chain can never settle another intent; only
+    // known once the presentation is verified unless the request stated it
+    let unchecked = verified.transaction_id.as_deref().filter(|_| request.payment_id.is_none());
+    if let (Some(chain), Some(payment_id)) = (&state.chain, unchecked) {
+        if let Err(e) = crate::chain::check_payment_unused(chain, payment_id).await {
+            return Err(attestation_failed(state, request, solver_address, start_time, e));
         }
     }
+
+    // Validate intent on-chain against the amount actually proven (if enabled)
+    let mut intent = None;
+    if let (Some(chain), Some(intent_bytes)) = (&state.chain, intent_bytes) {
+        match crate::chain::validate_intent(
+            chain,
+            intent_bytes,
+            solver_address,
+            &verified,
+            state.config.environment.is_strict(),
+        )
+        .await
+        {
+            Ok(validated) => intent = Some(validated),
+            Err(e) => {
+                let duration_ms = start_time.elapsed().as_millis() as u64;
+                state.audit.log(&AuditLogEntry {
+                    timestamp: current_timestamp(),
+                    solver_address: solver_address.to_string(),
+                    intent_hash: intent_hash.clone(),
+                    payment_id: verified.transaction_id.clone(),
+                    amount_cents: verified.amount_cents.unwrap_or(0),
+                    result: AuditResult::Rejected {
+                        reason: e.clone(),
+                    },
+                    request_ip: None,
+                    duration_ms,
+                });
+
+                warn!(
+                    intent_hash = %request.intent_hash,
+                    solver = %solver_address,
+                    error = %e,
+                    "Intent validation failed"
+                );
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(AuthErrorResponse {
+                        success: false,
+                        error: e,
+                    }),
+                )
+                    .into_response());
+            }
+        }
+    }
+
+    // Bind the payment to this intent; a payment attested for another intent
+    // is refused here rather than when the solver's transaction reverts
+    let claimed = match decode_bytes32(&request.intent_hash) {
+        Ok(intent_bytes) => {
+            let (payment, solver) = (verified.clone(), solver_address.to_string());
+            state
+                .with_store(move |store| {
+                    store
+                        .claim_payment(&payment, &intent_bytes, &solver, current_timestamp())
+                        .map(|_| intent_bytes)
+                })
+                .await
+        }
+        Err(e) => Err(AttestationError::DeserializationError(e)),
+    };
+    let intent_bytes = match claimed {
+        Ok(b) => b,
+        Err(e) => return Err(attestation_failed(state, request, solver_address, start_time, e)),
+    };
+
+    Ok((verified, intent, intent_bytes))
+}
+
+/// Authenticate the solver from its `X-Solver-API-Key` header (if auth is
+/// enabled), returning its address
+fn authenticate_solver(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<String, (StatusCode, Json<AuthErrorResponse>)> {
+    // Extract API key from header
+    let api_key = headers
+        .get("x-solver-api-key")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+
+    if state.auth.is_enabled() {
+        let key = match api_key {
+            Some(k) => k,
+            None => {
+                return Err((
+                    StatusCode::UNAUTHORIZED,
+                    Json(AuthErrorResponse {
+                        success: false,
+                        error: "Missing X-Solver-API-Key header".to_string(),
+                    }),
+                ));
+            }
+        };
+
+        match state.auth.validate_api_key(&key) {
+            Some(addr) => Ok(addr),
+            None => {
+                warn!(api_key = %key, "Invalid API key");
+                Err((
+                    StatusCode::UNAUTHORIZED,
+                    Json(AuthErrorResponse {
+                        success: false,
+                        error: "Invalid API key".to_string(),
+                    }),
+                ))
+            }
+        }
+    } else {
+        // Auth disabled, use placeholder
+        Ok("0x0000000000000000000000000000000000000000".to_string())
+    }
+}
+
+/// Audit-log a failed attestation and convert the error into a response
+fn attestation_failed(
+    state: &AppState,
+    request: &AttestationRequest,
+    solver_address: &str,
+    start_time: Instant,
+    e: AttestationError,
+) -> Response {
+    let duration_ms = start_time.elapsed().as_millis() as u64;
+    state.audit.log(&AuditLogEntry {
+        timestamp: current_timestamp(),
+        solver_address: solver_address.to_string(),
+        intent_hash: request.intent_hash.clone(),
+        payment_id: None,
+        amount_cents: request.expected_amount_cents,
+        result: AuditResult::Error {
+            message: e.to_string(),
+        },
+        request_ip: None,
+        duration_ms,
+    });
+
+    warn!(
+        intent_hash = %request.intent_hash,
+        error = %e,
+        "Attestation request failed"
+    );
+    e.into_response()
 }
 
 fn decode_bytes32(hex_str: &str) -> Result<[u8; 32], String> {
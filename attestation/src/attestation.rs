@@ -1,13 +1,22 @@
+use alloy_primitives::{keccak256, Address};
 use base64::Engine;
 use serde::{Deserialize, Serialize};
+use tracing::warn;
 
+use crate::audit::current_timestamp;
+use crate::chain::{Currency, OnChainIntent, FULFILLMENT_WINDOW_SECS};
+use crate::cluster::WitnessSignature;
 use crate::config::Config;
-use crate::eip712::{sign_attestation, AttestationData, AttestationDomain};
+use crate::eip712::{sign_attestation, AttestationData, AttestationDataV2, SchemaVersion};
 use crate::error::AttestationError;
-use crate::verification::{verify_presentation, VerifiedPayment};
+use crate::providers::ProviderRegistry;
+use crate::reference::check_payment_reference;
+use crate::verification::{
+    check_session_freshness, check_status_policy, PresentationVerifier, VerifiedPayment,
+};
 
 /// Request to create an attestation
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AttestationRequest {
     /// Base64-encoded TLSNotary presentation
     pub presentation: String,
@@ -19,11 +28,26 @@ pub struct AttestationRequest {
     pub expected_amount_cents: i64,
     
     /// Expected beneficiary IBAN (for validation)
+    ///
+    /// Advisory only when on-chain validation is enabled: the beneficiary is
+    /// then taken from the intent's `receivingInfo`.
     pub expected_beneficiary_iban: String,
+    
+    /// Transaction ID of the payment, if the solver knows it
+    ///
+    /// With on-chain validation it is checked against `usedTransferIds` and
+    /// the verifier's nullifiers before the presentation is verified. It must
+    /// then equal the proven transaction ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payment_id: Option<String>,
+    
+    /// Attestation schema to sign (1 or 2, default 1)
+    #[serde(default)]
+    pub schema_version: SchemaVersion,
 }
 
 /// Response containing the signed attestation
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AttestationResponse {
     /// Whether the attestation was successful
     pub success: bool,
@@ -31,41 +55,108 @@ pub struct AttestationResponse {
     /// The signed attestation (EIP-712 signature)
     pub signature: String,
     
+    /// In cluster mode, the signatures of every witness that signed the
+    /// digest, ordered by witness address
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signatures: Vec<WitnessSignature>,
+    
     /// The digest that was signed
     pub digest: String,
     
     /// Hash of the attestation data
     pub data_hash: String,
     
+    /// Schema of the signed attestation
+    #[serde(default)]
+    pub schema_version: SchemaVersion,
+    
+    /// Fields only schema v2 signs, needed to submit it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub v2: Option<AttestationV2Fields>,
+    
     /// Verified payment details
     pub payment: PaymentDetails,
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// Schema v2 fields of a signed attestation, as passed to the verifier
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttestationV2Fields {
+    pub currency: u8,
+    pub rtpn: u8,
+    pub solver: String,
+    pub beneficiary_hash: String,
+    pub executed_at: u64,
+    pub valid_until: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaymentDetails {
     pub transaction_id: Option<String>,
     pub amount_cents: i64,
+    /// ISO 4217 code of the amount
+    pub currency: String,
     pub beneficiary_iban: String,
+    /// Transaction status checked against the provider's status policy
+    pub status: String,
+    /// Transaction side checked against the provider's status policy
+    pub side: String,
     pub timestamp: u64,
     pub server: String,
 }
 
-/// Create a signed attestation from a TLSNotary presentation
-pub fn create_attestation(
+/// Verify the TLSNotary presentation in a request and validate the payment
+/// against the request's expectations and the intent's payment reference
+///
+/// With `onchain_beneficiary` set, the beneficiary is checked against the
+/// intent on-chain instead, and a mismatching request IBAN is only logged.
+pub fn verify_request(
     request: &AttestationRequest,
     config: &Config,
-) -> Result<AttestationResponse, AttestationError> {
+    verifier: &dyn PresentationVerifier,
+    providers: &ProviderRegistry,
+    onchain_beneficiary: bool,
+) -> Result<VerifiedPayment, AttestationError> {
     // Decode the presentation
     let presentation_bytes = base64::engine::general_purpose::STANDARD
         .decode(&request.presentation)
         .map_err(|e| AttestationError::DeserializationError(format!("Invalid base64: {}", e)))?;
     
     // Verify the TLSNotary presentation
-    let verified = verify_presentation(&presentation_bytes, &config.allowed_servers)?;
+    let verified = verifier.verify(
+        &presentation_bytes,
+        providers,
+        &config.trusted_notary_keys,
+    )?;
+    
+    // Reject stale or future-dated TLS sessions
+    check_session_freshness(verified.timestamp, current_timestamp(), config.max_session_age_secs)?;
+    
+    // Only settled, outgoing payments can be attested
+    let provider = providers
+        .get(&verified.server_name)
+        .ok_or(AttestationError::ServerNotFound)?;
+    check_status_policy(&verified, &provider.status_policy())?;
+    
+    // The transfer must carry this intent's reference, so one payment can't
+    // fill another intent with the same amount and beneficiary
+    check_payment_reference(verified.reference.as_deref(), &decode_bytes32(&request.intent_hash)?)?;
     
     // Validate the payment matches expectations
-    validate_payment(&verified, request)?;
+    validate_payment(&verified, request, onchain_beneficiary, config.environment.is_strict())?;
     
+    Ok(verified)
+}
+
+/// Create a signed attestation for a verified payment
+///
+/// Schema v2 takes the rail, solver and deadline from the on-chain `intent`,
+/// so it needs on-chain validation.
+pub async fn create_attestation(
+    request: &AttestationRequest,
+    verified: VerifiedPayment,
+    intent: Option<&OnChainIntent>,
+    config: &Config,
+) -> Result<AttestationResponse, AttestationError> {
     // Decode intent hash
     let intent_hash = decode_bytes32(&request.intent_hash)?;
     
@@ -78,36 +169,133 @@ pub fn create_attestation(
         data: verified.response_body.as_bytes().to_vec(),
     };
     
+    let data_hash = attestation_data.data_hash();
+    
     // Create EIP-712 domain
-    let domain = AttestationDomain::new(config.chain_id, config.verifier_contract);
+    let domain = config.domain(request.schema_version);
+    let signer = config.witness_keys.signer(current_timestamp())?;
     
     // Sign the attestation
-    let (signature, digest) = sign_attestation(&domain, &attestation_data, config.signing_key())?;
+    let (signature, digest, v2) = match request.schema_version {
+        SchemaVersion::V1 => {
+            let (signature, digest) = sign_attestation(&domain, &attestation_data, signer).await?;
+            (signature, digest, None)
+        }
+        SchemaVersion::V2 => {
+            let data = attestation_v2(attestation_data, &verified, intent)?;
+            let (signature, digest) = sign_attestation(&domain, &data, signer).await?;
+            let fields = AttestationV2Fields {
+                currency: data.currency,
+                rtpn: data.rtpn,
+                solver: format!("{}", Address::from(data.solver)),
+                beneficiary_hash: format!("0x{}", hex::encode(data.beneficiary_hash)),
+                executed_at: data.executed_at,
+                valid_until: data.valid_until,
+            };
+            (signature, digest, Some(fields))
+        }
+    };
     
     Ok(AttestationResponse {
         success: true,
         signature: format!("0x{}", hex::encode(signature)),
+        signatures: Vec::new(),
         digest: format!("0x{}", hex::encode(digest)),
-        data_hash: format!("0x{}", hex::encode(attestation_data.data_hash())),
+        data_hash: format!("0x{}", hex::encode(data_hash)),
+        schema_version: request.schema_version,
+        v2,
         payment: PaymentDetails {
             transaction_id: verified.transaction_id,
             amount_cents: verified.amount_cents.unwrap_or(0),
+            currency: verified.currency.unwrap_or_default(),
             beneficiary_iban: verified.beneficiary_iban.unwrap_or_default(),
+            status: verified.status.unwrap_or_default(),
+            side: verified.side.unwrap_or_default(),
             timestamp: verified.timestamp,
             server: verified.server_name,
         },
     })
 }
 
+/// Extend the v1 attestation data with what schema v2 binds.
+///
+/// Every field is derived from the presentation or the intent, so co-signing
+/// witnesses sign the same struct; the deadline is the end of the intent's
+/// fulfillment window.
+fn attestation_v2(
+    base: AttestationData,
+    verified: &VerifiedPayment,
+    intent: Option<&OnChainIntent>,
+) -> Result<AttestationDataV2, AttestationError> {
+    let intent = intent.ok_or_else(|| {
+        AttestationError::UnsupportedSchema(
+            "schema v2 requires on-chain validation (RPC_URL and OFFRAMP_CONTRACT)".to_string(),
+        )
+    })?;
+    
+    let currency = verified
+        .currency
+        .as_deref()
+        .ok_or_else(|| AttestationError::MissingField("currency".to_string()))?;
+    let currency = Currency::from_code(currency).ok_or_else(|| {
+        AttestationError::InvalidPaymentData(format!("Unsupported currency {}", currency))
+    })?;
+    let iban = verified
+        .beneficiary_iban
+        .as_deref()
+        .ok_or_else(|| AttestationError::MissingField("beneficiary_iban".to_string()))?;
+    let executed_at = verified
+        .executed_at
+        .ok_or_else(|| AttestationError::MissingField("executed_at".to_string()))?;
+    
+    Ok(AttestationDataV2 {
+        base,
+        currency: currency as u8,
+        rtpn: intent.selected_rtpn as u8,
+        solver: intent.solver.into(),
+        beneficiary_hash: keccak256(normalize_iban(iban).as_bytes()).0,
+        executed_at,
+        valid_until: intent.committed_at + FULFILLMENT_WINDOW_SECS,
+    })
+}
+
+/// Validate the verified payment against the request's expectations.
+///
+/// In strict mode the "expected 0 / empty" shortcuts are unavailable: the
+/// request must state a positive amount and, unless the beneficiary is checked
+/// on-chain, a beneficiary IBAN.
 fn validate_payment(
     verified: &VerifiedPayment,
     request: &AttestationRequest,
+    onchain_beneficiary: bool,
+    strict: bool,
 ) -> Result<(), AttestationError> {
+    // The payment ID checked on-chain up front must be the one proven
+    if let Some(expected) = request.payment_id.as_deref() {
+        let actual = verified.transaction_id.as_deref()
+            .ok_or_else(|| AttestationError::MissingField("transaction_id".to_string()))?;
+        
+        if actual != expected {
+            return Err(AttestationError::InvalidPaymentData(format!(
+                "Payment ID mismatch: expected {}, got {}",
+                expected,
+                actual
+            )));
+        }
+    }
+    
     // If expected values are 0/empty, skip validation (for testing)
-    if request.expected_amount_cents == 0 && request.expected_beneficiary_iban.is_empty() {
+    if !strict && request.expected_amount_cents == 0 && request.expected_beneficiary_iban.is_empty() {
         return Ok(());
     }
     
+    if strict && request.expected_amount_cents <= 0 {
+        return Err(AttestationError::InvalidPaymentData(format!(
+            "expected_amount_cents must be positive, got {}",
+            request.expected_amount_cents
+        )));
+    }
+    
     // Check amount matches (only if expected is non-zero)
     if request.expected_amount_cents > 0 {
         let actual_amount = verified.amount_cents
@@ -123,28 +311,124 @@ fn validate_payment(
     }
     
     // Check beneficiary IBAN matches (only if expected is non-empty)
-    if !request.expected_beneficiary_iban.is_empty() {
-        let expected_iban = normalize_iban(&request.expected_beneficiary_iban);
-        let actual_iban = verified.beneficiary_iban.as_ref()
-            .map(|s| normalize_iban(s))
-            .ok_or_else(|| AttestationError::MissingField("beneficiary_iban".to_string()))?;
-        
-        if expected_iban != actual_iban {
-            return Err(AttestationError::InvalidPaymentData(format!(
-                "IBAN mismatch: expected {}, got {}",
-                expected_iban,
-                actual_iban
-            )));
+    if request.expected_beneficiary_iban.is_empty() {
+        if strict && !onchain_beneficiary {
+            return Err(AttestationError::MissingField("expected_beneficiary_iban".to_string()));
+        }
+        return Ok(());
+    }
+    
+    let expected_iban = normalize_iban(&request.expected_beneficiary_iban);
+    let actual_iban = match verified.beneficiary_iban.as_ref() {
+        Some(iban) => normalize_iban(iban),
+        // The on-chain check reports a missing IBAN
+        None if onchain_beneficiary => return Ok(()),
+        None => return Err(AttestationError::MissingField("beneficiary_iban".to_string())),
+    };
+    
+    if expected_iban != actual_iban {
+        if onchain_beneficiary {
+            warn!(
+                expected = %expected_iban,
+                actual = %actual_iban,
+                "Request IBAN differs from proven IBAN (advisory, checked on-chain)"
+            );
+            return Ok(());
         }
+        
+        return Err(AttestationError::InvalidPaymentData(format!(
+            "IBAN mismatch: expected {}, got {}",
+            expected_iban,
+            actual_iban
+        )));
     }
     
     Ok(())
 }
 
-fn normalize_iban(iban: &str) -> String {
+pub(crate) fn normalize_iban(iban: &str) -> String {
     iban.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_uppercase()
 }
 
+/// Fuzzy match of a proven counterparty name against the expected recipient name.
+///
+/// Names are compared as sets of normalized tokens (case, accents and
+/// punctuation are ignored, order does not matter). Banks prefix and reorder
+/// names, so the proven name may carry extra tokens, but every token of the
+/// expected name must appear in it, each matching a different proven token
+/// and allowing one typo in tokens of 5+ characters. A proven name that is
+/// only part of the expected one (e.g. just a first name) does not match.
+pub(crate) fn names_match(actual: &str, expected: &str) -> bool {
+    let mut actual_tokens = name_tokens(actual);
+    let expected_tokens = name_tokens(expected);
+    
+    if expected_tokens.is_empty() {
+        return false;
+    }
+    
+    expected_tokens.iter().all(|token| {
+        // Prefer an exact match so a typo match doesn't take another token's place
+        let found = actual_tokens
+            .iter()
+            .position(|other| other == token)
+            .or_else(|| {
+                actual_tokens.iter().position(|other| {
+                    token.chars().count() >= 5 && levenshtein(token, other) <= 1
+                })
+            });
+        
+        match found {
+            Some(i) => {
+                actual_tokens.swap_remove(i);
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+/// Split a name into lowercase ASCII tokens, folding common accented letters
+fn name_tokens(name: &str) -> Vec<String> {
+    let folded: String = name
+        .chars()
+        .flat_map(|c| c.to_lowercase())
+        .map(fold_accent)
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { ' ' })
+        .collect();
+    
+    folded.split_whitespace().map(|t| t.to_string()).collect()
+}
+
+fn fold_accent(c: char) -> char {
+    match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => 'a',
+        'ç' => 'c',
+        'è' | 'é' | 'ê' | 'ë' => 'e',
+        'ì' | 'í' | 'î' | 'ï' => 'i',
+        'ñ' => 'n',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => 'o',
+        'ù' | 'ú' | 'û' | 'ü' => 'u',
+        'ý' | 'ÿ' => 'y',
+        _ => c,
+    }
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+    
+    prev[b.len()]
+}
+
 fn decode_bytes32(hex_str: &str) -> Result<[u8; 32], AttestationError> {
     let hex_str = hex_str.trim_start_matches("0x");
     let bytes = hex::decode(hex_str)
@@ -162,3 +446,153 @@ fn decode_bytes32(hex_str: &str) -> Result<[u8; 32], AttestationError> {
     Ok(arr)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn request(expected_amount_cents: i64, expected_beneficiary_iban: &str) -> AttestationRequest {
+        AttestationRequest {
+            presentation: String::new(),
+            intent_hash: format!("0x{}", "11".repeat(32)),
+            expected_amount_cents,
+            expected_beneficiary_iban: expected_beneficiary_iban.to_string(),
+            payment_id: None,
+            schema_version: SchemaVersion::V1,
+        }
+    }
+    
+    fn verified() -> VerifiedPayment {
+        VerifiedPayment {
+            amount_cents: Some(10000),
+            beneficiary_iban: Some("DE89370400440532013000".to_string()),
+            ..Default::default()
+        }
+    }
+    
+    #[test]
+    fn test_lenient_mode_allows_empty_expectations() {
+        assert!(validate_payment(&verified(), &request(0, ""), false, false).is_ok());
+    }
+    
+    #[test]
+    fn test_strict_mode_rejects_empty_expectations() {
+        assert!(validate_payment(&verified(), &request(0, ""), false, true).is_err());
+        assert!(validate_payment(&verified(), &request(-1, ""), true, true).is_err());
+        
+        // Without on-chain beneficiary binding, the request IBAN is required
+        assert!(matches!(
+            validate_payment(&verified(), &request(10000, ""), false, true),
+            Err(AttestationError::MissingField(_))
+        ));
+        assert!(validate_payment(&verified(), &request(10000, ""), true, true).is_ok());
+        assert!(validate_payment(&verified(), &request(10000, "DE89370400440532013000"), false, true).is_ok());
+    }
+    
+    #[test]
+    fn test_amount_mismatch_is_rejected() {
+        assert!(validate_payment(&verified(), &request(9999, ""), false, false).is_err());
+        assert!(validate_payment(&verified(), &request(9999, ""), true, true).is_err());
+    }
+    
+    #[test]
+    fn test_payment_id_must_match_proven_transaction() {
+        let proven = VerifiedPayment {
+            transaction_id: Some("tx-1".to_string()),
+            ..verified()
+        };
+        let stating = |payment_id: &str| AttestationRequest {
+            payment_id: Some(payment_id.to_string()),
+            ..request(0, "")
+        };
+        
+        assert!(validate_payment(&proven, &stating("tx-1"), false, false).is_ok());
+        // Checked even when the lenient shortcut skips everything else
+        assert!(matches!(
+            validate_payment(&proven, &stating("tx-2"), false, false),
+            Err(AttestationError::InvalidPaymentData(_))
+        ));
+        assert!(matches!(
+            validate_payment(&verified(), &stating("tx-1"), false, false),
+            Err(AttestationError::MissingField(_))
+        ));
+    }
+    
+    fn intent() -> OnChainIntent {
+        OnChainIntent {
+            owner: Address::repeat_byte(0x11),
+            usdc_amount: alloy_primitives::U256::from(100_000_000u64),
+            currency: Currency::Eur,
+            status: crate::chain::IntentStatus::Committed,
+            created_at: 1700000000,
+            committed_at: 1700000100,
+            solver: Address::repeat_byte(0x22),
+            selected_rtpn: crate::chain::Rtpn::SepaInstant,
+            selected_fiat_amount: alloy_primitives::U256::from(10000u64),
+            receiving_info: "DE89370400440532013000".to_string(),
+            recipient_name: "Max Mustermann".to_string(),
+            transfer_id: Default::default(),
+        }
+    }
+    
+    fn base() -> AttestationData {
+        AttestationData {
+            intent_hash: [0x11; 32],
+            amount: 10000,
+            timestamp: 1700000200,
+            payment_id: "tx-1".to_string(),
+            data: Vec::new(),
+        }
+    }
+    
+    #[test]
+    fn test_attestation_v2_fields() {
+        let verified = VerifiedPayment {
+            currency: Some("EUR".to_string()),
+            beneficiary_iban: Some("de89 3704 0044 0532 0130 00".to_string()),
+            executed_at: Some(1700000150),
+            ..verified()
+        };
+        
+        let data = attestation_v2(base(), &verified, Some(&intent())).unwrap();
+        assert_eq!(data.currency, Currency::Eur as u8);
+        assert_eq!(data.rtpn, 0);
+        assert_eq!(data.solver, [0x22; 20]);
+        assert_eq!(data.beneficiary_hash, keccak256(b"DE89370400440532013000").0);
+        assert_eq!(data.executed_at, 1700000150);
+        assert_eq!(data.valid_until, 1700000100 + FULFILLMENT_WINDOW_SECS);
+        
+        // Without the intent there is no rail, solver or deadline to bind
+        assert!(matches!(
+            attestation_v2(base(), &verified, None),
+            Err(AttestationError::UnsupportedSchema(_))
+        ));
+        
+        let unexecuted = VerifiedPayment { executed_at: None, ..verified.clone() };
+        assert!(matches!(
+            attestation_v2(base(), &unexecuted, Some(&intent())),
+            Err(AttestationError::MissingField(_))
+        ));
+    }
+    
+    #[test]
+    fn test_names_match() {
+        assert!(names_match("EI - MALYEN Malek", "Malek Malyen"));
+        assert!(names_match("MAX MUSTERMANN", "Max Mustermann"));
+        assert!(names_match("Jose Garcia", "José García"));
+        assert!(names_match("Max Musterman", "Max Mustermann"));
+        assert!(!names_match("Erika Musterfrau", "Max Mustermann"));
+        assert!(!names_match("Max", "Moritz Mustermann"));
+        assert!(!names_match("", "Max Mustermann"));
+        
+        // Extra tokens in the proven name are fine, missing ones are not
+        assert!(names_match("Max Mustermann GmbH", "Max Mustermann"));
+        assert!(!names_match("Max", "Max Mustermann"));
+        assert!(!names_match("Mustermann", "Max Mustermann"));
+        assert!(!names_match("GmbH", "Max Mustermann GmbH"));
+        assert!(!names_match("Max Mustermann", "Max Mustermann GmbH"));
+        
+        // Each expected token needs its own proven token
+        assert!(!names_match("Max", "Max Max"));
+        assert!(!names_match("Max Mustermann", ""));
+    }
+}
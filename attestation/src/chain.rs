@@ -1,9 +1,65 @@
 //! On-chain intent validation via RPC calls
 
-use alloy_primitives::{Address, FixedBytes, U256};
+use alloy_primitives::{keccak256, Address, B256, U256};
+use alloy_sol_types::{decode_revert_reason, sol, SolCall};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, warn};
 
+use crate::attestation::{names_match, normalize_iban, AttestationResponse};
+use crate::error::AttestationError;
+use crate::verification::VerifiedPayment;
+
+// ABI definitions matching contracts/src/OffRampV3.sol and PaymentVerifier.sol.
+// Solidity enums are ABI-encoded as uint8.
+sol! {
+    struct Intent {
+        address depositor;
+        uint256 usdcAmount;
+        uint8 currency;
+        uint8 status;
+        uint64 createdAt;
+        uint64 committedAt;
+        address selectedSolver;
+        uint8 selectedRtpn;
+        uint256 selectedFiatAmount;
+        string receivingInfo;
+        string recipientName;
+        bytes32 transferId;
+    }
+
+    function getIntent(bytes32 intentId) external view returns (Intent memory);
+
+    function usedTransferIds(bytes32 transferId) external view returns (bool);
+
+    struct PaymentAttestation {
+        bytes32 intentHash;
+        uint256 amount;
+        uint256 timestamp;
+        string paymentId;
+        bytes32 dataHash;
+    }
+
+    function fulfillIntentWithProof(
+        bytes32 intentId,
+        PaymentAttestation attestation,
+        bytes signature
+    ) external;
+
+    // PaymentVerifier.sol, which shares the PaymentAttestation struct
+    function isNullifierUsed(string paymentId) external view returns (bool);
+
+    function authorizedWitnesses(address witness) external view returns (bool);
+
+    function minWitnesses() external view returns (uint256);
+
+    function DOMAIN_SEPARATOR() external view returns (bytes32);
+
+    function getDigest(PaymentAttestation attestation) external view returns (bytes32);
+}
+
+/// Time window for the solver to fulfill after commitment (OffRampV3.FULFILLMENT_WINDOW)
+pub const FULFILLMENT_WINDOW_SECS: u64 = 30 * 60;
+
 /// On-chain intent status (matches OffRampV3.IntentStatus)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum IntentStatus {
@@ -28,21 +84,143 @@ impl From<u8> for IntentStatus {
     }
 }
 
-/// Intent data from on-chain
+/// Supported fiat currency (matches OffRampV3.Currency)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Currency {
+    Eur = 0,
+    Gbp = 1,
+    Usd = 2,
+    Brl = 3,
+    Inr = 4,
+}
+
+impl TryFrom<u8> for Currency {
+    type Error = String;
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        match v {
+            0 => Ok(Currency::Eur),
+            1 => Ok(Currency::Gbp),
+            2 => Ok(Currency::Usd),
+            3 => Ok(Currency::Brl),
+            4 => Ok(Currency::Inr),
+            _ => Err(format!("Unknown currency: {}", v)),
+        }
+    }
+}
+
+impl Currency {
+    /// ISO 4217 code
+    pub fn code(&self) -> &'static str {
+        match self {
+            Currency::Eur => "EUR",
+            Currency::Gbp => "GBP",
+            Currency::Usd => "USD",
+            Currency::Brl => "BRL",
+            Currency::Inr => "INR",
+        }
+    }
+
+    /// Look up a currency by ISO 4217 code (case-insensitive)
+    pub fn from_code(code: &str) -> Option<Self> {
+        [Currency::Eur, Currency::Gbp, Currency::Usd, Currency::Brl, Currency::Inr]
+            .into_iter()
+            .find(|c| c.code().eq_ignore_ascii_case(code.trim()))
+    }
+
+    /// Number of decimal places of the minor unit (cents, pence, centavos, paise)
+    pub fn minor_unit_exponent(&self) -> u32 {
+        match self {
+            Currency::Eur | Currency::Gbp | Currency::Usd | Currency::Brl | Currency::Inr => 2,
+        }
+    }
+}
+
+/// Real-time payment network (matches OffRampV3.RTPN)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rtpn {
+    SepaInstant = 0,
+    SepaStandard = 1,
+    Fps = 2,
+    Bacs = 3,
+    Pix = 4,
+    Ted = 5,
+    Upi = 6,
+    Imps = 7,
+    FedNow = 8,
+    Ach = 9,
+}
+
+impl TryFrom<u8> for Rtpn {
+    type Error = String;
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        match v {
+            0 => Ok(Rtpn::SepaInstant),
+            1 => Ok(Rtpn::SepaStandard),
+            2 => Ok(Rtpn::Fps),
+            3 => Ok(Rtpn::Bacs),
+            4 => Ok(Rtpn::Pix),
+            5 => Ok(Rtpn::Ted),
+            6 => Ok(Rtpn::Upi),
+            7 => Ok(Rtpn::Imps),
+            8 => Ok(Rtpn::FedNow),
+            9 => Ok(Rtpn::Ach),
+            _ => Err(format!("Unknown RTPN: {}", v)),
+        }
+    }
+}
+
+/// Intent data from on-chain (mirrors OffRampV3.Intent)
 #[derive(Debug, Clone)]
 pub struct OnChainIntent {
     pub owner: Address,
-    pub solver: Address,
     pub usdc_amount: U256,
+    pub currency: Currency,
+    pub status: IntentStatus,
+    pub created_at: u64,
+    /// When the user committed to a quote (0 if not committed yet)
+    pub committed_at: u64,
+    pub solver: Address,
+    pub selected_rtpn: Rtpn,
     /// The fiat amount the solver committed to pay (2 decimals, in cents)
     pub selected_fiat_amount: U256,
-    pub status: IntentStatus,
+    /// Network-specific receiving info (IBAN, sort code, PIX key, ...)
+    pub receiving_info: String,
+    pub recipient_name: String,
+    /// Fiat transfer ID recorded on fulfillment (zero until fulfilled)
+    pub transfer_id: B256,
+}
+
+impl TryFrom<Intent> for OnChainIntent {
+    type Error = String;
+
+    fn try_from(intent: Intent) -> Result<Self, Self::Error> {
+        Ok(Self {
+            owner: intent.depositor,
+            usdc_amount: intent.usdcAmount,
+            currency: Currency::try_from(intent.currency)?,
+            status: IntentStatus::from(intent.status),
+            created_at: intent.createdAt,
+            committed_at: intent.committedAt,
+            solver: intent.selectedSolver,
+            selected_rtpn: Rtpn::try_from(intent.selectedRtpn)?,
+            selected_fiat_amount: intent.selectedFiatAmount,
+            receiving_info: intent.receivingInfo,
+            recipient_name: intent.recipientName,
+            transfer_id: intent.transferId,
+        })
+    }
 }
 
 /// Chain client for RPC calls
 pub struct ChainClient {
     rpc_url: String,
     offramp_contract: Address,
+    /// PaymentVerifier contract, for nullifier checks
+    verifier_contract: Option<Address>,
+    /// Simulate fulfillment before returning an attestation
+    simulate_fulfillment: bool,
     http_client: reqwest::Client,
 }
 
@@ -65,10 +243,18 @@ impl ChainClient {
         Self {
             rpc_url,
             offramp_contract,
+            verifier_contract: None,
+            simulate_fulfillment: false,
             http_client: reqwest::Client::new(),
         }
     }
 
+    /// Also query the PaymentVerifier contract at `verifier_contract`
+    pub fn with_verifier(mut self, verifier_contract: Address) -> Self {
+        self.verifier_contract = Some(verifier_contract);
+        self
+    }
+
     /// Create from environment variables
     pub fn from_env() -> Option<Self> {
         let rpc_url = std::env::var("RPC_URL").ok()?;
@@ -80,73 +266,64 @@ impl ChainClient {
         }
 
         let offramp_contract = Address::from_slice(&offramp_bytes);
+        let client = Self::new(rpc_url, offramp_contract);
+
+        // The verifier is optional; without it nullifiers aren't checked
+        let verifier = std::env::var("VERIFIER_CONTRACT")
+            .ok()
+            .and_then(|v| hex::decode(v.trim_start_matches("0x")).ok())
+            .filter(|b| b.len() == 20)
+            .map(|b| Address::from_slice(&b))
+            .filter(|a| *a != Address::ZERO);
+
+        let client = match verifier {
+            Some(verifier) => client.with_verifier(verifier),
+            None => client,
+        };
+
+        let simulate = std::env::var("SIMULATE_FULFILLMENT")
+            .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        Some(if simulate { client.with_simulation() } else { client })
+    }
 
-        Some(Self::new(rpc_url, offramp_contract))
+    /// Simulate `fulfillIntentWithProof` before returning an attestation
+    pub fn with_simulation(mut self) -> Self {
+        self.simulate_fulfillment = true;
+        self
+    }
+
+    /// PaymentVerifier contract address, if configured
+    pub fn verifier_contract(&self) -> Option<Address> {
+        self.verifier_contract
     }
 
     /// Get intent from on-chain
     /// Calls: OffRampV3.getIntent(bytes32 intentId) returns (Intent)
+    ///
+    /// Returns `Ok(None)` if the intent does not exist (zero depositor) and an
+    /// error if the return data cannot be decoded as an `Intent`.
     pub async fn get_intent(&self, intent_hash: [u8; 32]) -> Result<Option<OnChainIntent>, String> {
-        // Function selector for getIntent(bytes32)
-        // keccak256("getIntent(bytes32)")[:4] = 0xf13c46aa
-        let selector = hex::decode("f13c46aa").unwrap();
-
-        let mut calldata = selector;
-        calldata.extend_from_slice(&intent_hash);
-
-        let result = self.eth_call(&calldata).await?;
-
-        // Response is a dynamic tuple with offset pointer at start
-        // Minimum size: 32 (offset) + 256 (first 8 fields) = 288 bytes
-        if result.len() < 288 {
-            // Intent doesn't exist or empty response
-            return Ok(None);
+        let calldata = getIntentCall {
+            intentId: B256::from(intent_hash),
         }
+        .abi_encode();
 
-        // Parse Intent struct (getIntent returns full struct as dynamic tuple):
-        // First 32 bytes are offset pointer (0x20), actual data starts at byte 32
-        // struct Intent {
-        //     address depositor;        // offset 32+0  = 32
-        //     uint256 usdcAmount;       // offset 32+32 = 64
-        //     Currency currency;        // offset 32+64 = 96  (uint8 padded to 32)
-        //     IntentStatus status;      // offset 32+96 = 128 (uint8 padded to 32)
-        //     uint64 createdAt;         // offset 32+128 = 160
-        //     uint64 committedAt;       // offset 32+160 = 192
-        //     address selectedSolver;   // offset 32+192 = 224
-        //     RTPN selectedRtpn;        // offset 32+224 = 256 (uint8 padded to 32)
-        //     uint256 selectedFiatAmount; // offset 32+256 = 288
-        //     ...
-        // }
-
-        let base = 32; // Skip offset pointer
-
-        let depositor = Address::from_slice(&result[base + 12..base + 32]);
-        let usdc_amount = U256::from_be_slice(&result[base + 32..base + 64]);
-        // currency at base+64..base+96 (not needed for validation)
-        let status = IntentStatus::from(result[base + 96 + 31]); // Last byte of status word
-        // createdAt at base+128..base+160
-        // committedAt at base+160..base+192
-        let selected_solver = Address::from_slice(&result[base + 192 + 12..base + 224]);
-        // selectedRtpn at base+224..base+256 (not needed for validation)
-        // selectedFiatAmount at base+256..base+288 (this is in cents, 2 decimals)
-        let selected_fiat_amount = if result.len() >= base + 288 {
-            U256::from_be_slice(&result[base + 256..base + 288])
-        } else {
-            U256::ZERO
-        };
+        let result = self.eth_call(self.offramp_contract, &calldata).await?;
+
+        let intent = getIntentCall::abi_decode_returns(&result, true)
+            .map_err(|e| format!("Failed to decode getIntent return data: {}", e))?
+            ._0;
 
         // Check if intent exists (depositor is not zero)
-        if depositor == Address::ZERO {
+        if intent.depositor == Address::ZERO {
             return Ok(None);
         }
 
-        Ok(Some(OnChainIntent {
-            owner: depositor,
-            solver: selected_solver,
-            usdc_amount,
-            selected_fiat_amount,
-            status,
-        }))
+        OnChainIntent::try_from(intent)
+            .map(Some)
+            .map_err(|e| format!("Invalid intent data: {}", e))
     }
 
     /// Check if an address is an authorized solver
@@ -167,7 +344,7 @@ impl ChainClient {
         calldata.extend_from_slice(&[0u8; 12]); // Pad to 32 bytes
         calldata.extend_from_slice(&solver_bytes);
 
-        let result = self.eth_call(&calldata).await?;
+        let result = self.eth_call(self.offramp_contract, &calldata).await?;
 
         // Result is 32 bytes, last byte is boolean
         if result.len() < 32 {
@@ -177,9 +354,159 @@ impl ChainClient {
         Ok(result[31] != 0)
     }
 
+    /// Whether a payment ID was already used to fulfill an intent
+    /// Calls: OffRampV3.usedTransferIds(keccak256(paymentId)) returns (bool)
+    pub async fn is_transfer_id_used(&self, payment_id: &str) -> Result<bool, String> {
+        let calldata = usedTransferIdsCall {
+            transferId: keccak256(payment_id.as_bytes()),
+        }
+        .abi_encode();
+
+        let result = self.eth_call(self.offramp_contract, &calldata).await?;
+
+        usedTransferIdsCall::abi_decode_returns(&result, true)
+            .map(|r| r._0)
+            .map_err(|e| format!("Failed to decode usedTransferIds return data: {}", e))
+    }
+
+    /// Whether a payment ID's nullifier was consumed by the verifier, or
+    /// `None` without a verifier contract
+    /// Calls: PaymentVerifier.isNullifierUsed(string paymentId) returns (bool)
+    pub async fn is_nullifier_used(&self, payment_id: &str) -> Result<Option<bool>, String> {
+        let Some(verifier) = self.verifier_contract else {
+            return Ok(None);
+        };
+
+        let calldata = isNullifierUsedCall {
+            paymentId: payment_id.to_string(),
+        }
+        .abi_encode();
+
+        let result = self.eth_call(verifier, &calldata).await?;
+
+        isNullifierUsedCall::abi_decode_returns(&result, true)
+            .map(|r| Some(r._0))
+            .map_err(|e| format!("Failed to decode isNullifierUsed return data: {}", e))
+    }
+
+    /// Whether the verifier trusts `witness`, or `None` without a verifier
+    /// contract
+    /// Calls: PaymentVerifier.authorizedWitnesses(address) returns (bool)
+    pub async fn is_witness_authorized(&self, witness: Address) -> Result<Option<bool>, String> {
+        let Some(verifier) = self.verifier_contract else {
+            return Ok(None);
+        };
+
+        let calldata = authorizedWitnessesCall { witness }.abi_encode();
+
+        let result = self.eth_call(verifier, &calldata).await?;
+
+        authorizedWitnessesCall::abi_decode_returns(&result, true)
+            .map(|r| Some(r._0))
+            .map_err(|e| format!("Failed to decode authorizedWitnesses return data: {}", e))
+    }
+
+    /// Number of witness signatures the verifier asks for, or `None` without
+    /// a verifier contract
+    /// Calls: PaymentVerifier.minWitnesses() returns (uint256)
+    pub async fn verifier_min_witnesses(&self) -> Result<Option<U256>, String> {
+        let Some(verifier) = self.verifier_contract else {
+            return Ok(None);
+        };
+
+        let calldata = minWitnessesCall {}.abi_encode();
+
+        let result = self.eth_call(verifier, &calldata).await?;
+
+        minWitnessesCall::abi_decode_returns(&result, true)
+            .map(|r| Some(r._0))
+            .map_err(|e| format!("Failed to decode minWitnesses return data: {}", e))
+    }
+
+    /// The verifier's EIP-712 domain separator, or `None` without a verifier
+    /// contract
+    /// Calls: PaymentVerifier.DOMAIN_SEPARATOR() returns (bytes32)
+    pub async fn verifier_domain_separator(&self) -> Result<Option<B256>, String> {
+        let Some(verifier) = self.verifier_contract else {
+            return Ok(None);
+        };
+
+        let calldata = DOMAIN_SEPARATORCall {}.abi_encode();
+
+        let result = self.eth_call(verifier, &calldata).await?;
+
+        DOMAIN_SEPARATORCall::abi_decode_returns(&result, true)
+            .map(|r| Some(r._0))
+            .map_err(|e| format!("Failed to decode DOMAIN_SEPARATOR return data: {}", e))
+    }
+
+    /// The verifier's EIP-712 digest of an attestation, or `None` without a
+    /// verifier contract
+    /// Calls: PaymentVerifier.getDigest(PaymentAttestation) returns (bytes32)
+    pub async fn verifier_digest(
+        &self,
+        attestation: PaymentAttestation,
+    ) -> Result<Option<B256>, String> {
+        let Some(verifier) = self.verifier_contract else {
+            return Ok(None);
+        };
+
+        let calldata = getDigestCall { attestation }.abi_encode();
+
+        let result = self.eth_call(verifier, &calldata).await?;
+
+        getDigestCall::abi_decode_returns(&result, true)
+            .map(|r| Some(r._0))
+            .map_err(|e| format!("Failed to decode getDigest return data: {}", e))
+    }
+
+    /// Simulate `OffRampV3.fulfillIntentWithProof` from `solver` with a
+    /// freshly signed attestation
+    ///
+    /// Returns the decoded revert reason if the call would revert, and an
+    /// error if the simulation itself failed.
+    pub async fn simulate_fulfillment(
+        &self,
+        solver: Address,
+        attestation: PaymentAttestation,
+        signature: Vec<u8>,
+    ) -> Result<Option<String>, String> {
+        let calldata = fulfillIntentWithProofCall {
+            intentId: attestation.intentHash,
+            attestation,
+            signature: signature.into(),
+        }
+        .abi_encode();
+
+        match self.call(Some(solver), self.offramp_contract, &calldata).await {
+            Ok(_) => Ok(None),
+            Err(CallError::Reverted(data)) => Ok(Some(decode_revert(&data))),
+            Err(CallError::Rpc(e)) => Err(e),
+        }
+    }
+
+    /// Whether fulfillment is simulated before an attestation is returned
+    pub fn simulates_fulfillment(&self) -> bool {
+        self.simulate_fulfillment
+    }
+
     /// Make an eth_call RPC request
-    async fn eth_call(&self, calldata: &[u8]) -> Result<Vec<u8>, String> {
-        let to_addr = format!("0x{}", hex::encode(self.offramp_contract.as_slice()));
+    async fn eth_call(&self, to: Address, calldata: &[u8]) -> Result<Vec<u8>, String> {
+        self.call(None, to, calldata).await.map_err(|e| match e {
+            CallError::Reverted(data) => format!("RPC error: {}", decode_revert(&data)),
+            CallError::Rpc(e) => e,
+        })
+    }
+
+    /// Make an eth_call RPC request, optionally from an address, telling
+    /// reverts apart from RPC failures
+    async fn call(
+        &self,
+        from: Option<Address>,
+        to: Address,
+        calldata: &[u8],
+    ) -> Result<Vec<u8>, CallError> {
+        let to_addr = format!("0x{}", hex::encode(to.as_slice()));
         let data_hex = format!("0x{}", hex::encode(calldata));
 
         debug!(
@@ -189,16 +516,18 @@ impl ChainClient {
             "Making eth_call"
         );
 
+        let mut tx = serde_json::json!({
+            "to": to_addr,
+            "data": data_hex,
+        });
+        if let Some(from) = from {
+            tx["from"] = serde_json::json!(format!("0x{}", hex::encode(from.as_slice())));
+        }
+
         let request = JsonRpcRequest {
             jsonrpc: "2.0",
             method: "eth_call",
-            params: vec![
-                serde_json::json!({
-                    "to": to_addr,
-                    "data": data_hex,
-                }),
-                serde_json::json!("latest"),
-            ],
+            params: vec![tx, serde_json::json!("latest")],
             id: 1,
         };
 
@@ -208,15 +537,15 @@ impl ChainClient {
             .json(&request)
             .send()
             .await
-            .map_err(|e| format!("RPC request failed: {}", e))?;
+            .map_err(|e| CallError::Rpc(format!("RPC request failed: {}", e)))?;
 
         let json_response: JsonRpcResponse = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse RPC response: {}", e))?;
+            .map_err(|e| CallError::Rpc(format!("Failed to parse RPC response: {}", e)))?;
 
         if let Some(error) = json_response.error {
-            return Err(format!("RPC error: {:?}", error));
+            return Err(CallError::from_rpc_error(error));
         }
 
         let result_hex = json_response.result.unwrap_or_default();
@@ -226,17 +555,193 @@ impl ChainClient {
             return Ok(vec![]);
         }
 
-        hex::decode(result_hex).map_err(|e| format!("Failed to decode result: {}", e))
+        hex::decode(result_hex).map_err(|e| CallError::Rpc(format!("Failed to decode result: {}", e)))
+    }
+}
+
+/// Failure of an eth_call
+enum CallError {
+    /// The call reverted, with the revert data (empty if the node gave none)
+    Reverted(Vec<u8>),
+    /// The RPC request itself failed
+    Rpc(String),
+}
+
+impl CallError {
+    /// Nodes report a revert as a JSON-RPC error whose `data` holds the
+    /// revert data, or at least whose message mentions the revert
+    fn from_rpc_error(error: serde_json::Value) -> Self {
+        let data = error
+            .get("data")
+            .and_then(|d| d.as_str())
+            .and_then(|d| hex::decode(d.trim_start_matches("0x")).ok());
+        let message = error.get("message").and_then(|m| m.as_str()).unwrap_or_default();
+
+        match data {
+            Some(data) => CallError::Reverted(data),
+            None if message.contains("revert") => CallError::Reverted(Vec::new()),
+            None => CallError::Rpc(format!("RPC error: {:?}", error)),
+        }
+    }
+}
+
+/// Custom errors `fulfillIntentWithProof` can revert with, from OffRampV3,
+/// PaymentVerifier and the OpenZeppelin contracts they use
+const FULFILLMENT_ERRORS: &[&str] = &[
+    "IntentNotFound()",
+    "InvalidIntentStatus()",
+    "NotSelectedSolver()",
+    "FulfillmentWindowExpired()",
+    "PaymentVerificationFailed()",
+    "AmountMismatch()",
+    "TransferIdAlreadyUsed()",
+    "NullifierAlreadyUsed()",
+    "NotAuthorizedWitness()",
+    "InvalidSignature()",
+    "EnforcedPause()",
+    "ReentrancyGuardReentrantCall()",
+    "ECDSAInvalidSignature()",
+    "ECDSAInvalidSignatureLength(uint256)",
+    "ECDSAInvalidSignatureS(bytes32)",
+];
+
+/// Human-readable reason for revert data: a known custom error name,
+/// `Error(string)` / `Panic(uint256)`, or the raw data
+pub fn decode_revert(data: &[u8]) -> String {
+    if data.len() < 4 {
+        return "execution reverted without reason".to_string();
+    }
+
+    let custom = FULFILLMENT_ERRORS
+        .iter()
+        .find(|signature| keccak256(signature.as_bytes())[..4] == data[..4]);
+    if let Some(signature) = custom {
+        return signature.split('(').next().unwrap_or(signature).to_string();
+    }
+
+    decode_revert_reason(data)
+        .unwrap_or_else(|| format!("unknown revert 0x{}", hex::encode(data)))
+}
+
+/// Reject an intent already fulfilled on-chain. Cheap enough to run before
+/// the presentation is verified.
+pub async fn check_intent_unfulfilled(
+    chain: &ChainClient,
+    intent_hash: [u8; 32],
+) -> Result<(), AttestationError> {
+    let intent = chain
+        .get_intent(intent_hash)
+        .await
+        .map_err(|e| AttestationError::Internal(format!("On-chain check failed: {}", e)))?;
+
+    match intent {
+        Some(intent) if intent.status == IntentStatus::Fulfilled => {
+            Err(AttestationError::AlreadyFulfilled(format!(
+                "intent 0x{} was fulfilled with transfer ID {}",
+                hex::encode(intent_hash),
+                intent.transfer_id
+            )))
+        }
+        // Any other problem with the intent is reported by `validate_intent`
+        _ => Ok(()),
+    }
+}
+
+/// Reject a payment ID already consumed on-chain, either as an OffRampV3
+/// transfer ID or as a PaymentVerifier nullifier. Either way the attestation
+/// could never be settled.
+pub async fn check_payment_unused(
+    chain: &ChainClient,
+    payment_id: &str,
+) -> Result<(), AttestationError> {
+    let rpc_failed =
+        |e: String| AttestationError::Internal(format!("On-chain check failed: {}", e));
+
+    let transfer_id_used = chain
+        .is_transfer_id_used(payment_id)
+        .await
+        .map_err(rpc_failed)?;
+    if transfer_id_used {
+        return Err(AttestationError::AlreadyFulfilled(format!(
+            "payment {} was already used as a transfer ID on-chain",
+            payment_id
+        )));
+    }
+
+    let nullifier_used = chain
+        .is_nullifier_used(payment_id)
+        .await
+        .map_err(rpc_failed)?;
+    if nullifier_used == Some(true) {
+        return Err(AttestationError::AlreadyFulfilled(format!(
+            "payment {} nullifier was already used on-chain",
+            payment_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Simulate the solver's `fulfillIntentWithProof` call with a signed
+/// attestation, rejecting an attestation that could never land on-chain.
+///
+/// A failed simulation (e.g. RPC error) is only logged: the attestation may
+/// still be valid.
+pub async fn check_fulfillment(
+    chain: &ChainClient,
+    solver_address: &str,
+    intent_hash: [u8; 32],
+    response: &AttestationResponse,
+) -> Result<(), AttestationError> {
+    let invalid = |what: &str, e: String| {
+        AttestationError::Internal(format!("Cannot simulate fulfillment: invalid {}: {}", what, e))
+    };
+    let decode = |what: &str, value: &str| {
+        hex::decode(value.trim_start_matches("0x")).map_err(|e| invalid(what, e.to_string()))
+    };
+
+    let solver = solver_address
+        .parse::<Address>()
+        .map_err(|e| invalid("solver address", e.to_string()))?;
+    let data_hash = decode("data hash", &response.data_hash)?;
+    if data_hash.len() != 32 {
+        return Err(invalid("data hash", format!("{} bytes", data_hash.len())));
+    }
+
+    let attestation = PaymentAttestation {
+        intentHash: B256::from(intent_hash),
+        amount: U256::from(response.payment.amount_cents as u64),
+        timestamp: U256::from(response.payment.timestamp),
+        paymentId: response.payment.transaction_id.clone().unwrap_or_default(),
+        dataHash: B256::from_slice(&data_hash),
+    };
+    let signature = decode("signature", &response.signature)?;
+
+    match chain.simulate_fulfillment(solver, attestation, signature).await {
+        Ok(None) => Ok(()),
+        Ok(Some(reason)) => Err(AttestationError::FulfillmentWouldRevert(reason)),
+        Err(e) => {
+            warn!(error = %e, "Fulfillment simulation failed, returning attestation unchecked");
+            Ok(())
+        }
     }
 }
 
 /// Validate an intent before creating attestation
+///
+/// The payment is checked against values taken from the verified TLSNotary
+/// transcript, never against values supplied by the solver. Returns the
+/// on-chain intent on success.
+///
+/// In `strict` mode an intent without a committed fiat amount is rejected
+/// instead of skipping the amount check.
 pub async fn validate_intent(
     chain: &ChainClient,
     intent_hash: [u8; 32],
     solver_address: &str,
-    expected_amount_cents: i64,
-) -> Result<(), String> {
+    verified: &VerifiedPayment,
+    strict: bool,
+) -> Result<OnChainIntent, String> {
     debug!(
         intent_hash = %hex::encode(intent_hash),
         solver = %solver_address,
@@ -275,30 +780,116 @@ pub async fn validate_intent(
     // The selectedSolver check above is sufficient to verify the solver
     // is authorized to fulfill this specific intent
 
+    // The payment must be in the intent's currency before amounts compare
+    validate_currency(&intent, verified)?;
+
     // Validate fiat amount: the proof amount must be >= the committed fiat amount
-    // Both values are in cents (2 decimals)
+    // Both values are in the currency's minor unit
     let committed_fiat_cents = intent.selected_fiat_amount.to::<u128>() as i64;
 
-    if expected_amount_cents > 0 && committed_fiat_cents > 0 {
-        if expected_amount_cents < committed_fiat_cents {
+    if committed_fiat_cents > 0 {
+        let proven_amount_cents = verified
+            .amount_cents
+            .ok_or_else(|| "Proof does not contain a payment amount".to_string())?;
+
+        if proven_amount_cents < committed_fiat_cents {
             return Err(format!(
                 "Amount mismatch: proof shows {} cents paid, but solver committed to {} cents on-chain",
-                expected_amount_cents, committed_fiat_cents
+                proven_amount_cents, committed_fiat_cents
             ));
         }
 
         debug!(
-            proof_amount_cents = %expected_amount_cents,
+            proof_amount_cents = %proven_amount_cents,
             committed_fiat_cents = %committed_fiat_cents,
             "Fiat amount validated: proof >= committed"
         );
-    } else if committed_fiat_cents == 0 {
+    } else if strict {
+        return Err("Intent has no committed fiat amount on-chain (selectedFiatAmount=0)".to_string());
+    } else {
         // Intent may not have a selected quote yet, or legacy data
         warn!(
             "No committed fiat amount on-chain (selectedFiatAmount=0), skipping amount validation"
         );
     }
 
+    validate_session_time(&intent, verified.timestamp)?;
+
+    validate_beneficiary(&intent, verified)?;
+
+    Ok(intent)
+}
+
+/// Check the proven payment is in the intent's currency
+fn validate_currency(intent: &OnChainIntent, verified: &VerifiedPayment) -> Result<(), String> {
+    let actual = verified
+        .currency
+        .as_deref()
+        .ok_or_else(|| "Proof does not contain a payment currency".to_string())?;
+
+    if !actual.eq_ignore_ascii_case(intent.currency.code()) {
+        return Err(format!(
+            "Currency mismatch: intent is in {}, proof shows {}",
+            intent.currency.code(),
+            actual
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check the TLS session happened between the intent's commitment and its
+/// fulfillment deadline. A payment proven before the commitment can't be for
+/// this intent, and one after the deadline can no longer be settled on-chain.
+fn validate_session_time(intent: &OnChainIntent, session_time: u64) -> Result<(), String> {
+    if session_time < intent.committed_at {
+        return Err(format!(
+            "TLS session at {} predates intent commitment at {}",
+            session_time, intent.committed_at
+        ));
+    }
+
+    let deadline = intent.committed_at + FULFILLMENT_WINDOW_SECS;
+    if session_time > deadline {
+        return Err(format!(
+            "TLS session at {} is after the fulfillment deadline {}",
+            session_time, deadline
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check the proven beneficiary against the intent's receivingInfo and recipientName
+fn validate_beneficiary(intent: &OnChainIntent, verified: &VerifiedPayment) -> Result<(), String> {
+    let expected_iban = normalize_iban(&intent.receiving_info);
+    let actual_iban = verified
+        .beneficiary_iban
+        .as_deref()
+        .map(normalize_iban)
+        .ok_or_else(|| "Proof does not contain a beneficiary IBAN".to_string())?;
+
+    if expected_iban != actual_iban {
+        return Err(format!(
+            "IBAN mismatch: intent receivingInfo is {}, proof shows {}",
+            expected_iban, actual_iban
+        ));
+    }
+
+    let actual_name = verified
+        .beneficiary_name
+        .as_deref()
+        .ok_or_else(|| "Proof does not contain a beneficiary name".to_string())?;
+
+    if !names_match(actual_name, &intent.recipient_name) {
+        return Err(format!(
+            "Recipient name mismatch: intent recipientName is {:?}, proof shows {:?}",
+            intent.recipient_name, actual_name
+        ));
+    }
+
+    debug!(iban = %actual_iban, name = %actual_name, "Beneficiary validated against intent");
+
     Ok(())
 }
 
@@ -316,4 +907,36 @@ mod tests {
         assert_eq!(IntentStatus::from(5), IntentStatus::Expired);
         assert_eq!(IntentStatus::from(99), IntentStatus::None);
     }
+
+    #[test]
+    fn test_currency_and_rtpn_try_from() {
+        assert_eq!(Currency::try_from(0), Ok(Currency::Eur));
+        assert_eq!(Currency::try_from(4), Ok(Currency::Inr));
+        assert!(Currency::try_from(5).is_err());
+
+        assert_eq!(Currency::from_code("gbp"), Some(Currency::Gbp));
+        assert_eq!(Currency::from_code("JPY"), None);
+        assert_eq!(Currency::Inr.code(), "INR");
+        assert_eq!(Rtpn::try_from(0), Ok(Rtpn::SepaInstant));
+        assert_eq!(Rtpn::try_from(9), Ok(Rtpn::Ach));
+        assert!(Rtpn::try_from(10).is_err());
+    }
+
+    #[test]
+    fn test_decode_revert() {
+        use alloy_sol_types::{Revert, SolError};
+
+        let selector = |signature: &str| keccak256(signature.as_bytes())[..4].to_vec();
+
+        assert_eq!(decode_revert(&selector("NotSelectedSolver()")), "NotSelectedSolver");
+        assert_eq!(decode_revert(&selector("NullifierAlreadyUsed()")), "NullifierAlreadyUsed");
+
+        let mut length_error = selector("ECDSAInvalidSignatureLength(uint256)");
+        length_error.extend_from_slice(&[0u8; 32]);
+        assert_eq!(decode_revert(&length_error), "ECDSAInvalidSignatureLength");
+
+        assert_eq!(decode_revert(&Revert::from("paused").abi_encode()), "revert: paused");
+        assert_eq!(decode_revert(&[]), "execution reverted without reason");
+        assert_eq!(decode_revert(&[0xde, 0xad, 0xbe, 0xef]), "unknown revert 0xdeadbeef");
+    }
 }
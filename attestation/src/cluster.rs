@@ -0,0 +1,298 @@
+//! Multi-witness co-signing
+//!
+//! An attestation can carry signatures from several witnesses. In cluster
+//! mode the instance a solver calls (the primary) signs as usual, then
+//! forwards the request to the peer witness instances listed in
+//! `WITNESS_PEERS`. Each peer verifies the presentation and the intent again
+//! on its own, signs with its own key and returns its attestation. The primary
+//! keeps the peer signatures over the same digest and returns the set, ordered
+//! by witness address, once it holds `MIN_WITNESSES` of them.
+//!
+//! Peers accept co-sign requests only with the shared `CLUSTER_API_KEY` in the
+//! `X-Cluster-Key` header.
+//!
+//! The threshold is enforced here only: `PaymentVerifier.verifyPayment`
+//! checks a single signature from any authorized witness and never reads
+//! `minWitnesses`, so on-chain one witness key still suffices.
+
+use std::time::Duration;
+
+use alloy_primitives::{Address, B256};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinSet;
+use tracing::warn;
+
+use crate::attestation::{AttestationRequest, AttestationResponse};
+use crate::error::AttestationError;
+use crate::signer::recover_address;
+
+/// Header carrying the cluster key on co-sign requests
+pub const CLUSTER_KEY_HEADER: &str = "x-cluster-key";
+
+/// Time allowed for a peer to verify the payment and sign
+const COSIGN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Attestation request forwarded to a peer witness
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CosignRequest {
+    pub request: AttestationRequest,
+    /// Solver the primary authenticated, which the intent must name
+    pub solver_address: String,
+}
+
+/// One witness's signature of a co-signed attestation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WitnessSignature {
+    pub witness: String,
+    pub signature: String,
+}
+
+/// Peer witnesses and the number of signatures an attestation needs
+pub struct WitnessCluster {
+    peers: Vec<String>,
+    cluster_key: Option<String>,
+    min_witnesses: usize,
+    http_client: reqwest::Client,
+}
+
+impl WitnessCluster {
+    pub fn new(
+        peers: Vec<String>,
+        cluster_key: Option<String>,
+        min_witnesses: usize,
+    ) -> Result<Self> {
+        if min_witnesses == 0 {
+            return Err(anyhow!("MIN_WITNESSES must be at least 1"));
+        }
+        if min_witnesses > peers.len() + 1 {
+            return Err(anyhow!(
+                "MIN_WITNESSES is {} but only {} witnesses are configured (this one and {} in WITNESS_PEERS)",
+                min_witnesses,
+                peers.len() + 1,
+                peers.len()
+            ));
+        }
+        if !peers.is_empty() && cluster_key.is_none() {
+            return Err(anyhow!("WITNESS_PEERS requires CLUSTER_API_KEY"));
+        }
+
+        let http_client = reqwest::Client::builder()
+            .timeout(COSIGN_TIMEOUT)
+            .build()
+            .map_err(|e| anyhow!("Cannot create HTTP client: {}", e))?;
+
+        Ok(Self {
+            peers: peers
+                .into_iter()
+                .map(|url| url.trim_end_matches('/').to_string())
+                .collect(),
+            cluster_key,
+            min_witnesses,
+            http_client,
+        })
+    }
+
+    /// Create from `WITNESS_PEERS` (comma-separated base URLs),
+    /// `CLUSTER_API_KEY` and `MIN_WITNESSES` (default 1)
+    pub fn from_env() -> Result<Self> {
+        let peers = std::env::var("WITNESS_PEERS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(str::to_string)
+            .collect();
+        let cluster_key = std::env::var("CLUSTER_API_KEY")
+            .ok()
+            .filter(|key| !key.is_empty());
+        let min_witnesses = std::env::var("MIN_WITNESSES")
+            .unwrap_or_else(|_| "1".to_string())
+            .parse()
+            .map_err(|e| anyhow!("Invalid MIN_WITNESSES: {}", e))?;
+
+        Self::new(peers, cluster_key, min_witnesses)
+    }
+
+    pub fn peers(&self) -> &[String] {
+        &self.peers
+    }
+
+    /// Signatures an attestation needs, this instance's included
+    pub fn min_witnesses(&self) -> usize {
+        self.min_witnesses
+    }
+
+    /// Whether attestations are co-signed by peers
+    pub fn is_enabled(&self) -> bool {
+        !self.peers.is_empty()
+    }
+
+    /// Whether a co-sign request carries the cluster key
+    pub fn accepts(&self, key: Option<&str>) -> bool {
+        matches!((&self.cluster_key, key), (Some(expected), Some(key)) if expected == key)
+    }
+
+    /// Have the peers co-sign the attestation this instance signed in
+    /// `response`, returning every signature of its digest ordered by
+    /// witness address.
+    ///
+    /// Peers that fail, refuse, or sign anything else are skipped; fewer
+    /// than `min_witnesses` signatures is an error.
+    pub async fn cosign(
+        &self,
+        request: &AttestationRequest,
+        solver_address: &str,
+        response: &AttestationResponse,
+    ) -> Result<Vec<WitnessSignature>, AttestationError> {
+        let digest = decode_digest(&response.digest).map_err(AttestationError::Internal)?;
+        let own = signer_of(&response.signature, &digest)?;
+        let mut signatures = vec![(own, response.signature.clone())];
+        let mut failures = Vec::new();
+
+        let body = CosignRequest {
+            request: request.clone(),
+            solver_address: solver_address.to_string(),
+        };
+        let key = self.cluster_key.clone().unwrap_or_default();
+
+        let mut tasks = JoinSet::new();
+        for peer in &self.peers {
+            let http_client = self.http_client.clone();
+            let (peer, key, body) = (peer.clone(), key.clone(), body.clone());
+            tasks.spawn(async move {
+                let result = request_signature(&http_client, &peer, &key, &body).await;
+                (peer, result)
+            });
+        }
+
+        while let Some(joined) = tasks.join_next().await {
+            let (peer, result) = joined
+                .map_err(|e| AttestationError::Internal(format!("Co-sign task failed: {}", e)))?;
+
+            match result.and_then(|peer_response| peer_signature(&peer_response, &digest)) {
+                Ok((witness, _)) if signatures.iter().any(|(w, _)| *w == witness) => {
+                    failures.push(format!("{}: duplicate witness {}", peer, witness));
+                }
+                Ok(signature) => signatures.push(signature),
+                Err(e) => failures.push(format!("{}: {}", peer, e)),
+            }
+        }
+
+        for failure in &failures {
+            warn!(intent_hash = %request.intent_hash, "Peer witness did not co-sign: {}", failure);
+        }
+
+        if signatures.len() < self.min_witnesses {
+            return Err(AttestationError::InsufficientWitnesses {
+                collected: signatures.len(),
+                required: self.min_witnesses,
+                detail: failures.join("; "),
+            });
+        }
+
+        signatures.sort_by_key(|(witness, _)| *witness);
+        Ok(signatures
+            .into_iter()
+            .map(|(witness, signature)| WitnessSignature {
+                witness: format!("{}", witness),
+                signature,
+            })
+            .collect())
+    }
+}
+
+/// Ask one peer to verify and sign
+async fn request_signature(
+    http_client: &reqwest::Client,
+    peer: &str,
+    key: &str,
+    body: &CosignRequest,
+) -> Result<AttestationResponse, String> {
+    let response = http_client
+        .post(format!("{}/api/v1/cosign", peer))
+        .header(CLUSTER_KEY_HEADER, key)
+        .json(body)
+        .send()
+        .await
+        .map_err(|e| format!("request failed: {}", e))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        // Peers answer errors as {"error": ...}
+        let body = response.text().await.unwrap_or_default();
+        let reason = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|v| v.get("error").and_then(|e| e.as_str()).map(str::to_string))
+            .unwrap_or(body);
+        return Err(format!("{}: {}", status, reason.trim()));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| format!("invalid response: {}", e))
+}
+
+/// Witness and signature of a peer's attestation, which must sign `digest`
+fn peer_signature(
+    response: &AttestationResponse,
+    digest: &B256,
+) -> Result<(Address, String), String> {
+    let peer_digest = decode_digest(&response.digest)?;
+    if peer_digest != *digest {
+        return Err(format!(
+            "signed digest {} instead of {}",
+            peer_digest, digest
+        ));
+    }
+
+    let witness = signer_of(&response.signature, digest).map_err(|e| e.to_string())?;
+    Ok((witness, response.signature.clone()))
+}
+
+fn signer_of(signature: &str, digest: &B256) -> Result<Address, AttestationError> {
+    let bytes = hex::decode(signature.trim_start_matches("0x"))
+        .map_err(|e| AttestationError::SigningError(format!("invalid signature hex: {}", e)))?;
+    recover_address(digest, &bytes)
+}
+
+fn decode_digest(digest: &str) -> Result<B256, String> {
+    digest
+        .parse()
+        .map_err(|e| format!("invalid digest {}: {}", digest, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cluster_configuration() {
+        let peers = || vec!["http://w2:4001/".to_string(), "http://w3:4001".to_string()];
+        let key = || Some("secret".to_string());
+
+        let cluster = WitnessCluster::new(peers(), key(), 3).unwrap();
+        assert!(cluster.is_enabled());
+        assert_eq!(cluster.peers()[0], "http://w2:4001");
+
+        assert!(WitnessCluster::new(peers(), key(), 0).is_err());
+        assert!(WitnessCluster::new(peers(), key(), 4).is_err());
+        assert!(WitnessCluster::new(peers(), None, 2).is_err());
+        assert!(WitnessCluster::new(vec![], None, 2).is_err());
+        assert!(!WitnessCluster::new(vec![], None, 1).unwrap().is_enabled());
+    }
+
+    #[test]
+    fn test_accepts_only_the_cluster_key() {
+        let cluster = WitnessCluster::new(vec![], Some("secret".to_string()), 1).unwrap();
+        assert!(cluster.accepts(Some("secret")));
+        assert!(!cluster.accepts(Some("other")));
+        assert!(!cluster.accepts(None));
+
+        // Without a key, co-signing is disabled
+        let cluster = WitnessCluster::new(vec![], None, 1).unwrap();
+        assert!(!cluster.accepts(None));
+        assert!(!cluster.accepts(Some("")));
+    }
+}
@@ -1,10 +1,67 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
 use anyhow::{anyhow, Result};
-use k256::ecdsa::SigningKey;
+
+use crate::audit::current_timestamp;
+use crate::eip712::{AttestationDomain, SchemaVersion};
+use crate::server_policy::{parse_server_policy, ServerPolicyEntry};
+use crate::witness::WitnessKeys;
+
+/// Deployment environment profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Environment {
+    /// Local development: validation shortcuts allowed
+    Development,
+    /// Staging: strict payment validation, auth and chain checks optional
+    Staging,
+    /// Production: strict payment validation, auth and chain checks required
+    Production,
+}
+
+impl Environment {
+    /// Whether the "expected 0 / empty" validation shortcuts are disabled
+    pub fn is_strict(&self) -> bool {
+        !matches!(self, Environment::Development)
+    }
+    
+    /// Whether solver auth and on-chain validation must be enabled to start
+    pub fn requires_safeguards(&self) -> bool {
+        matches!(self, Environment::Production)
+    }
+}
+
+impl FromStr for Environment {
+    type Err = anyhow::Error;
+    
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "dev" | "development" => Ok(Environment::Development),
+            "staging" => Ok(Environment::Staging),
+            "prod" | "production" => Ok(Environment::Production),
+            other => Err(anyhow!("Invalid ENVIRONMENT: {} (expected development, staging or production)", other)),
+        }
+    }
+}
+
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Environment::Development => write!(f, "development"),
+            Environment::Staging => write!(f, "staging"),
+            Environment::Production => write!(f, "production"),
+        }
+    }
+}
 
 /// Configuration for the attestation service
 pub struct Config {
-    /// The private key used to sign attestations (ECDSA secp256k1)
-    signing_key: SigningKey,
+    /// Deployment environment profile
+    pub environment: Environment,
+    
+    /// Witness keys (ECDSA secp256k1) and their validity windows
+    pub witness_keys: WitnessKeys,
     
     /// Chain ID for EIP-712 domain separator
     pub chain_id: u64,
@@ -12,21 +69,39 @@ pub struct Config {
     /// Verifier contract address for EIP-712 domain separator
     pub verifier_contract: [u8; 20],
     
-    /// Allowed server domains for presentation verification
-    pub allowed_servers: Vec<String>,
+    /// EIP-712 domain name and version of schema v1 attestations
+    pub domain_name: String,
+    pub domain_version: String,
+    
+    /// EIP-712 domain name, version and verifying contract of schema v2
+    /// attestations
+    pub domain_name_v2: String,
+    pub domain_version_v2: String,
+    pub verifier_contract_v2: [u8; 20],
+    
+    /// Allowed TLS server names for presentation verification
+    pub server_policy: Vec<ServerPolicyEntry>,
+    
+    /// Trusted notary public keys (compressed SEC1 secp256k1, 33 bytes each)
+    pub trusted_notary_keys: Vec<Vec<u8>>,
+    
+    /// Maximum age of a TLS session, in seconds, for its proof to be attested
+    pub max_session_age_secs: u64,
+    
+    /// SQLite database recording which intent each payment was attested for
+    pub database_path: PathBuf,
 }
 
 impl Config {
     pub fn from_env() -> Result<Self> {
-        // Load signing key from environment
-        let key_hex = std::env::var("WITNESS_PRIVATE_KEY")
-            .map_err(|_| anyhow!("WITNESS_PRIVATE_KEY not set"))?;
+        // Load environment profile. Required: a deployment that forgot it
+        // must not silently fall back to the lenient development profile.
+        let environment = std::env::var("ENVIRONMENT")
+            .map_err(|_| anyhow!("ENVIRONMENT must be set (development, staging or production)"))?
+            .parse()?;
         
-        let key_bytes = hex::decode(key_hex.trim_start_matches("0x"))
-            .map_err(|e| anyhow!("Invalid WITNESS_PRIVATE_KEY hex: {}", e))?;
-        
-        let signing_key = SigningKey::from_bytes((&key_bytes[..]).into())
-            .map_err(|e| anyhow!("Invalid WITNESS_PRIVATE_KEY: {}", e))?;
+        // Load the witness keys (WITNESS_KEYS, or the single WITNESS_* key)
+        let witness_keys = WitnessKeys::from_env()?;
         
         // Load chain ID (default to Base Sepolia for testing)
         let chain_id = std::env::var("CHAIN_ID")
@@ -38,46 +113,138 @@ impl Config {
         let verifier_hex = std::env::var("VERIFIER_CONTRACT")
             .unwrap_or_else(|_| "0x0000000000000000000000000000000000000000".to_string());
         
-        let verifier_bytes = hex::decode(verifier_hex.trim_start_matches("0x"))
-            .map_err(|e| anyhow!("Invalid VERIFIER_CONTRACT hex: {}", e))?;
+        let verifier_contract = parse_address("VERIFIER_CONTRACT", &verifier_hex)?;
         
-        let mut verifier_contract = [0u8; 20];
-        if verifier_bytes.len() != 20 {
-            return Err(anyhow!("VERIFIER_CONTRACT must be 20 bytes"));
-        }
-        verifier_contract.copy_from_slice(&verifier_bytes);
+        // Load EIP-712 domains (v1 defaults match deployed verifiers)
+        let domain_name = std::env::var("DOMAIN_NAME")
+            .unwrap_or_else(|_| AttestationDomain::default().name);
+        let domain_version = std::env::var("DOMAIN_VERSION")
+            .unwrap_or_else(|_| AttestationDomain::default().version);
+        let domain_name_v2 = std::env::var("DOMAIN_NAME_V2").unwrap_or_else(|_| domain_name.clone());
+        let domain_version_v2 = std::env::var("DOMAIN_VERSION_V2").unwrap_or_else(|_| "2".to_string());
+        let verifier_contract_v2 = match std::env::var("VERIFIER_CONTRACT_V2") {
+            Ok(hex) => parse_address("VERIFIER_CONTRACT_V2", &hex)?,
+            Err(_) => verifier_contract,
+        };
+        
+        // Load allowed servers (exact names or `*.` wildcards, optionally `=provider`)
+        let server_policy = parse_server_policy(
+            &std::env::var("ALLOWED_SERVERS").unwrap_or_else(|_| "thirdparty.qonto.com".to_string()),
+        )
+        .map_err(|e| anyhow!("Invalid ALLOWED_SERVERS: {}", e))?;
+        
+        // Load trusted notary keys (each environment pins its own notary)
+        let trusted_notary_keys = parse_notary_keys(
+            &std::env::var("TRUSTED_NOTARY_KEYS")
+                .map_err(|_| anyhow!("TRUSTED_NOTARY_KEYS not set"))?,
+        )?;
+        
+        // Load maximum TLS session age (default: 1 hour)
+        let max_session_age_secs = std::env::var("MAX_SESSION_AGE_SECS")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse()
+            .map_err(|e| anyhow!("Invalid MAX_SESSION_AGE_SECS: {}", e))?;
         
-        // Load allowed servers
-        let allowed_servers = std::env::var("ALLOWED_SERVERS")
-            .unwrap_or_else(|_| "thirdparty.qonto.com".to_string())
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .collect();
+        // Load replay store location
+        let database_path = std::env::var("DATABASE_PATH")
+            .unwrap_or_else(|_| "data/attestation.db".to_string())
+            .into();
         
         Ok(Self {
-            signing_key,
+            environment,
+            witness_keys,
             chain_id,
             verifier_contract,
-            allowed_servers,
+            domain_name,
+            domain_version,
+            domain_name_v2,
+            domain_version_v2,
+            verifier_contract_v2,
+            server_policy,
+            trusted_notary_keys,
+            max_session_age_secs,
+            database_path,
         })
     }
     
-    pub fn signing_key(&self) -> &SigningKey {
-        &self.signing_key
+    /// EIP-712 domain attestations of `schema` are signed under
+    pub fn domain(&self, schema: SchemaVersion) -> AttestationDomain {
+        match schema {
+            SchemaVersion::V1 => AttestationDomain::named(
+                &self.domain_name,
+                &self.domain_version,
+                self.chain_id,
+                self.verifier_contract,
+            ),
+            SchemaVersion::V2 => AttestationDomain::named(
+                &self.domain_name_v2,
+                &self.domain_version_v2,
+                self.chain_id,
+                self.verifier_contract_v2,
+            ),
+        }
+    }
+    
+    /// Address of the witness key currently signing, if any
+    pub fn witness_address(&self) -> Option<[u8; 20]> {
+        self.witness_keys
+            .primary(current_timestamp())
+            .map(|key| key.signer.address().into())
+    }
+}
+
+/// Parse a 20-byte hex address from the variable `name`
+fn parse_address(name: &str, value: &str) -> Result<[u8; 20]> {
+    let bytes = hex::decode(value.trim_start_matches("0x"))
+        .map_err(|e| anyhow!("Invalid {} hex: {}", name, e))?;
+    
+    let mut address = [0u8; 20];
+    if bytes.len() != 20 {
+        return Err(anyhow!("{} must be 20 bytes", name));
     }
+    address.copy_from_slice(&bytes);
+    Ok(address)
+}
+
+/// Parse a comma-separated list of hex-encoded secp256k1 notary public keys.
+/// Keys may be given compressed or uncompressed; they are stored compressed,
+/// which is the encoding TLSNotary uses for the attestation verifying key.
+fn parse_notary_keys(value: &str) -> Result<Vec<Vec<u8>>> {
+    use k256::elliptic_curve::sec1::ToEncodedPoint;
     
-    pub fn witness_address(&self) -> [u8; 20] {
-        use k256::ecdsa::VerifyingKey;
-        use alloy_primitives::keccak256;
+    let mut keys = Vec::new();
+    
+    for key_hex in value.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let key_bytes = hex::decode(key_hex.trim_start_matches("0x"))
+            .map_err(|e| anyhow!("Invalid TRUSTED_NOTARY_KEYS hex: {}", e))?;
         
-        let verifying_key = VerifyingKey::from(&self.signing_key);
-        let pubkey_bytes = verifying_key.to_encoded_point(false);
+        let public_key = k256::PublicKey::from_sec1_bytes(&key_bytes)
+            .map_err(|e| anyhow!("Invalid TRUSTED_NOTARY_KEYS entry {}: {}", key_hex, e))?;
         
-        // Keccak256 hash of public key (without prefix byte), take last 20 bytes
-        let hash = keccak256(&pubkey_bytes.as_bytes()[1..]);
-        let mut addr = [0u8; 20];
-        addr.copy_from_slice(&hash[12..]);
-        addr
+        keys.push(public_key.to_encoded_point(true).as_bytes().to_vec());
+    }
+    
+    if keys.is_empty() {
+        return Err(anyhow!("TRUSTED_NOTARY_KEYS must contain at least one key"));
     }
+    
+    Ok(keys)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_parse_environment() {
+        assert_eq!("dev".parse::<Environment>().unwrap(), Environment::Development);
+        assert_eq!("Staging".parse::<Environment>().unwrap(), Environment::Staging);
+        assert_eq!("production".parse::<Environment>().unwrap(), Environment::Production);
+        assert!("prodution".parse::<Environment>().is_err());
+        
+        assert!(!Environment::Development.is_strict());
+        assert!(Environment::Staging.is_strict());
+        assert!(!Environment::Staging.requires_safeguards());
+        assert!(Environment::Production.requires_safeguards());
+    }
+}
@@ -1,8 +1,10 @@
-use alloy_primitives::{keccak256, B256, U256};
-use alloy_sol_types::sol;
-use k256::ecdsa::{signature::hazmat::PrehashSigner, Signature, SigningKey};
+use alloy_primitives::{keccak256, Address, B256, U256};
+use alloy_sol_types::{sol, Eip712Domain, SolStruct};
+use serde::{Deserialize, Serialize};
 
 use crate::error::AttestationError;
+use crate::signer::WitnessSigner;
+use crate::typed_data::{self, TypedData};
 
 // Define EIP-712 types matching ZKP2P's PaymentAttestation
 sol! {
@@ -13,6 +15,54 @@ sol! {
         string paymentId;
         bytes32 dataHash;
     }
+
+    struct PaymentAttestationV2 {
+        bytes32 intentHash;
+        uint256 amount;
+        uint256 timestamp;
+        string paymentId;
+        bytes32 dataHash;
+        uint8 currency;
+        uint8 rtpn;
+        address solver;
+        bytes32 beneficiaryHash;
+        uint256 executedAt;
+        uint256 validUntil;
+    }
+}
+
+/// Version of the signed attestation struct, chosen per request
+///
+/// V1 (`PaymentAttestation`) is what deployed verifiers check. V2
+/// (`PaymentAttestationV2`) also binds the currency, rail, solver, beneficiary,
+/// bank execution time and a deadline.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum SchemaVersion {
+    #[default]
+    V1,
+    V2,
+}
+
+impl TryFrom<u8> for SchemaVersion {
+    type Error = String;
+    
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        match v {
+            1 => Ok(SchemaVersion::V1),
+            2 => Ok(SchemaVersion::V2),
+            other => Err(format!("unsupported schema version {} (expected 1 or 2)", other)),
+        }
+    }
+}
+
+impl From<SchemaVersion> for u8 {
+    fn from(version: SchemaVersion) -> u8 {
+        match version {
+            SchemaVersion::V1 => 1,
+            SchemaVersion::V2 => 2,
+        }
+    }
 }
 
 /// EIP-712 Domain for payment attestations
@@ -36,6 +86,7 @@ impl Default for AttestationDomain {
 }
 
 impl AttestationDomain {
+    /// Domain with the default name and version of deployed verifiers
     pub fn new(chain_id: u64, verifying_contract: [u8; 20]) -> Self {
         Self {
             chain_id,
@@ -44,27 +95,42 @@ impl AttestationDomain {
         }
     }
     
+    /// Domain with an explicit name and version
+    pub fn named(name: &str, version: &str, chain_id: u64, verifying_contract: [u8; 20]) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            chain_id,
+            verifying_contract,
+        }
+    }
+    
+    /// The domain as typed data
+    pub fn eip712_domain(&self) -> Eip712Domain {
+        typed_data::domain(
+            &self.name,
+            &self.version,
+            self.chain_id,
+            Address::from(self.verifying_contract),
+        )
+    }
+    
     /// Compute the EIP-712 domain separator
     pub fn domain_separator(&self) -> B256 {
-        let type_hash = keccak256(
-            b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)"
-        );
-        
-        let name_hash = keccak256(self.name.as_bytes());
-        let version_hash = keccak256(self.version.as_bytes());
-        
-        let mut encoded = Vec::new();
-        encoded.extend_from_slice(&type_hash[..]);
-        encoded.extend_from_slice(&name_hash[..]);
-        encoded.extend_from_slice(&version_hash[..]);
-        encoded.extend_from_slice(&U256::from(self.chain_id).to_be_bytes::<32>());
-        
-        // Pad address to 32 bytes
-        let mut addr_padded = [0u8; 32];
-        addr_padded[12..].copy_from_slice(&self.verifying_contract);
-        encoded.extend_from_slice(&addr_padded);
-        
-        keccak256(&encoded)
+        self.eip712_domain().separator()
+    }
+}
+
+/// An attestation struct that can be signed under an EIP-712 domain
+pub trait TypedAttestation {
+    /// The `sol!` struct the verifier hashes
+    type Struct: SolStruct;
+    
+    fn to_sol(&self) -> Self::Struct;
+    
+    /// EIP-712 `hashStruct` of the attestation
+    fn struct_hash(&self) -> B256 {
+        self.to_sol().eip712_hash_struct()
     }
 }
 
@@ -78,68 +144,94 @@ pub struct AttestationData {
     pub data: Vec<u8>,
 }
 
-impl AttestationData {
-    /// Compute the struct hash for EIP-712 signing
-    pub fn struct_hash(&self) -> B256 {
-        let type_hash = keccak256(
-            b"PaymentAttestation(bytes32 intentHash,uint256 amount,uint256 timestamp,string paymentId,bytes32 dataHash)"
-        );
-        
-        let payment_id_hash = keccak256(self.payment_id.as_bytes());
-        let data_hash = keccak256(&self.data);
-        
-        let mut encoded = Vec::new();
-        encoded.extend_from_slice(&type_hash[..]);
-        encoded.extend_from_slice(&self.intent_hash);
-        encoded.extend_from_slice(&U256::from(self.amount).to_be_bytes::<32>());
-        encoded.extend_from_slice(&U256::from(self.timestamp).to_be_bytes::<32>());
-        encoded.extend_from_slice(&payment_id_hash[..]);
-        encoded.extend_from_slice(&data_hash[..]);
-        
-        keccak256(&encoded)
-    }
+impl TypedAttestation for AttestationData {
+    type Struct = PaymentAttestation;
     
+    fn to_sol(&self) -> PaymentAttestation {
+        PaymentAttestation {
+            intentHash: B256::from(self.intent_hash),
+            amount: U256::from(self.amount),
+            timestamp: U256::from(self.timestamp),
+            paymentId: self.payment_id.clone(),
+            dataHash: self.data_hash(),
+        }
+    }
+}
+
+impl AttestationData {
     pub fn data_hash(&self) -> B256 {
         keccak256(&self.data)
     }
 }
 
+/// Schema v2 attestation: the v1 fields plus what the payment was bound to
+#[derive(Debug, Clone)]
+pub struct AttestationDataV2 {
+    pub base: AttestationData,
+    /// `OffRampV3.Currency` of the proven amount
+    pub currency: u8,
+    /// `OffRampV3.RTPN` the intent selected
+    pub rtpn: u8,
+    /// Solver the intent selected
+    pub solver: [u8; 20],
+    /// keccak256 of the normalized beneficiary IBAN
+    pub beneficiary_hash: [u8; 32],
+    /// When the bank executed the transfer (unix seconds)
+    pub executed_at: u64,
+    /// Last second the attestation may be used on-chain
+    pub valid_until: u64,
+}
+
+impl TypedAttestation for AttestationDataV2 {
+    type Struct = PaymentAttestationV2;
+    
+    fn to_sol(&self) -> PaymentAttestationV2 {
+        let base = self.base.to_sol();
+        PaymentAttestationV2 {
+            intentHash: base.intentHash,
+            amount: base.amount,
+            timestamp: base.timestamp,
+            paymentId: base.paymentId,
+            dataHash: base.dataHash,
+            currency: self.currency,
+            rtpn: self.rtpn,
+            solver: Address::from(self.solver),
+            beneficiaryHash: B256::from(self.beneficiary_hash),
+            executedAt: U256::from(self.executed_at),
+            validUntil: U256::from(self.valid_until),
+        }
+    }
+}
+
+/// EIP-712 message of an attestation, whose keccak256 is the signed digest
+pub fn attestation_message(domain: &AttestationDomain, data: &impl TypedAttestation) -> Vec<u8> {
+    TypedData::new(domain.eip712_domain(), data.to_sol()).encode()
+}
+
+/// EIP-712 digest of an attestation, as computed by `PaymentVerifier.getDigest`
+pub fn attestation_digest(domain: &AttestationDomain, data: &impl TypedAttestation) -> B256 {
+    keccak256(attestation_message(domain, data))
+}
+
 /// Sign an attestation using EIP-712
-pub fn sign_attestation(
+pub async fn sign_attestation(
     domain: &AttestationDomain,
-    data: &AttestationData,
-    signing_key: &SigningKey,
+    data: &(impl TypedAttestation + Sync),
+    signer: &dyn WitnessSigner,
 ) -> Result<([u8; 65], B256), AttestationError> {
-    let domain_separator = domain.domain_separator();
-    let struct_hash = data.struct_hash();
+    let message = attestation_message(domain, data);
     
-    // EIP-712: \x19\x01 || domain_separator || struct_hash
-    let mut message = Vec::with_capacity(66);
-    message.push(0x19);
-    message.push(0x01);
-    message.extend_from_slice(&domain_separator[..]);
-    message.extend_from_slice(&struct_hash[..]);
+    // The signer signs keccak256(message), i.e. the digest
+    let signature = signer.sign(&message).await?;
     
-    let digest = keccak256(&message);
-    
-    // Sign the digest using prehash signing
-    let (signature, recovery_id) = signing_key
-        .sign_prehash_recoverable(&digest[..])
-        .map_err(|e| AttestationError::SigningError(format!("Failed to sign: {}", e)))?;
-    
-    // Encode as 65-byte signature: r (32) || s (32) || v (1)
-    let mut sig_bytes = [0u8; 65];
-    sig_bytes[..32].copy_from_slice(&signature.r().to_bytes());
-    sig_bytes[32..64].copy_from_slice(&signature.s().to_bytes());
-    sig_bytes[64] = recovery_id.to_byte() + 27; // Ethereum v value
-    
-    Ok((sig_bytes, digest))
+    Ok((signature, keccak256(&message)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use k256::ecdsa::VerifyingKey;
+    use crate::signer::LocalSigner;
+    use k256::ecdsa::{SigningKey, VerifyingKey};
     
     #[test]
     fn test_domain_separator() {
@@ -148,8 +240,8 @@ mod tests {
         assert!(!separator.is_zero());
     }
     
-    #[test]
-    fn test_sign_attestation() {
+    #[tokio::test]
+    async fn test_sign_attestation() {
         let domain = AttestationDomain::default();
         let data = AttestationData {
             intent_hash: [1u8; 32],
@@ -160,9 +252,9 @@ mod tests {
         };
         
         // Generate a test key
-        let signing_key = SigningKey::random(&mut rand::thread_rng());
+        let signer = LocalSigner::new(SigningKey::random(&mut rand::thread_rng()));
         
-        let (signature, digest) = sign_attestation(&domain, &data, &signing_key).unwrap();
+        let (signature, digest) = sign_attestation(&domain, &data, &signer).await.unwrap();
         
         // Verify signature length
         assert_eq!(signature.len(), 65);
@@ -171,5 +263,63 @@ mod tests {
         let v = signature[64];
         assert!(v == 27 || v == 28);
     }
+    
+    #[test]
+    fn test_named_domain() {
+        let contract = [0x33u8; 20];
+        assert_eq!(
+            AttestationDomain::named("WisePaymentVerifier", "1", 84532, contract).domain_separator(),
+            AttestationDomain::new(84532, contract).domain_separator()
+        );
+        assert_ne!(
+            AttestationDomain::named("WisePaymentVerifier", "2", 84532, contract).domain_separator(),
+            AttestationDomain::new(84532, contract).domain_separator()
+        );
+    }
+    
+    #[test]
+    fn test_v2_struct_hash_matches_sol_struct() {
+        let data = AttestationDataV2 {
+            base: AttestationData {
+                intent_hash: [1u8; 32],
+                amount: 10000,
+                timestamp: 1703500000,
+                payment_id: "tx-123".to_string(),
+                data: b"test data".to_vec(),
+            },
+            currency: 1,
+            rtpn: 2,
+            solver: [0x22u8; 20],
+            beneficiary_hash: keccak256(b"GB29NWBK60161331926819").0,
+            executed_at: 1703499900,
+            valid_until: 1703501800,
+        };
+        
+        let sol_struct = PaymentAttestationV2 {
+            intentHash: B256::from(data.base.intent_hash),
+            amount: U256::from(data.base.amount),
+            timestamp: U256::from(data.base.timestamp),
+            paymentId: data.base.payment_id.clone(),
+            dataHash: data.base.data_hash(),
+            currency: data.currency,
+            rtpn: data.rtpn,
+            solver: Address::from(data.solver),
+            beneficiaryHash: B256::from(data.beneficiary_hash),
+            executedAt: U256::from(data.executed_at),
+            validUntil: U256::from(data.valid_until),
+        };
+        assert_eq!(data.struct_hash(), sol_struct.eip712_hash_struct());
+        
+        // V2 never hashes like the V1 struct of the same payment
+        assert_ne!(data.struct_hash(), data.base.struct_hash());
+    }
+    
+    #[test]
+    fn test_schema_version_json() {
+        assert_eq!(serde_json::from_str::<SchemaVersion>("1").unwrap(), SchemaVersion::V1);
+        assert_eq!(serde_json::from_str::<SchemaVersion>("2").unwrap(), SchemaVersion::V2);
+        assert!(serde_json::from_str::<SchemaVersion>("3").is_err());
+        assert_eq!(serde_json::to_string(&SchemaVersion::V2).unwrap(), "2");
+    }
 }
 
@@ -26,6 +26,39 @@ pub enum AttestationError {
     #[error("Unexpected server: expected {expected}, got {actual}")]
     UnexpectedServer { expected: String, actual: String },
 
+    #[error("Untrusted notary key: {0}")]
+    UntrustedNotary(String),
+
+    #[error("Invalid HTTP transcript: {0}")]
+    InvalidHttpTranscript(String),
+
+    #[error("Invalid TLS session time: {0}")]
+    InvalidSessionTime(String),
+
+    #[error("Payment status not accepted: {0}")]
+    InvalidPaymentStatus(String),
+
+    #[error("Payment reference does not match intent: {0}")]
+    InvalidPaymentReference(String),
+
+    #[error("Payment {transaction_id} was already attested for intent {intent_hash}")]
+    PaymentAlreadyAttested { transaction_id: String, intent_hash: String },
+
+    #[error("Intent {0} was already attested for another presentation; fetch it from /api/v1/attestations")]
+    IntentAlreadyAttested(String),
+
+    #[error("Already fulfilled: {0}")]
+    AlreadyFulfilled(String),
+
+    #[error("Fulfillment would revert: {0}")]
+    FulfillmentWouldRevert(String),
+
+    #[error("Only {collected} of {required} witness signatures collected: {detail}")]
+    InsufficientWitnesses { collected: usize, required: usize, detail: String },
+
+    #[error("Unsupported attestation schema: {0}")]
+    UnsupportedSchema(String),
+
     #[error("Missing required field: {0}")]
     MissingField(String),
 
@@ -48,6 +81,17 @@ impl IntoResponse for AttestationError {
             AttestationError::ServerNotFound => (StatusCode::BAD_REQUEST, self.to_string()),
             AttestationError::TranscriptNotFound => (StatusCode::BAD_REQUEST, self.to_string()),
             AttestationError::UnexpectedServer { .. } => (StatusCode::BAD_REQUEST, self.to_string()),
+            AttestationError::UntrustedNotary(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            AttestationError::InvalidHttpTranscript(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            AttestationError::InvalidSessionTime(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            AttestationError::InvalidPaymentStatus(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            AttestationError::InvalidPaymentReference(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            AttestationError::PaymentAlreadyAttested { .. } => (StatusCode::CONFLICT, self.to_string()),
+            AttestationError::IntentAlreadyAttested(_) => (StatusCode::CONFLICT, self.to_string()),
+            AttestationError::AlreadyFulfilled(_) => (StatusCode::CONFLICT, self.to_string()),
+            AttestationError::FulfillmentWouldRevert(_) => (StatusCode::UNPROCESSABLE_ENTITY, self.to_string()),
+            AttestationError::InsufficientWitnesses { .. } => (StatusCode::SERVICE_UNAVAILABLE, self.to_string()),
+            AttestationError::UnsupportedSchema(_) => (StatusCode::BAD_REQUEST, self.to_string()),
             AttestationError::MissingField(_) => (StatusCode::BAD_REQUEST, self.to_string()),
             AttestationError::SigningError(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
             AttestationError::DeserializationError(_) => (StatusCode::BAD_REQUEST, self.to_string()),
@@ -0,0 +1,355 @@
+//! HTTP parsing and checks for TLSNotary transcripts
+
+use std::ops::Range;
+
+use spansy::http::{parse_request, Request};
+use spansy::Spanned;
+
+use crate::error::AttestationError;
+
+/// An HTTP endpoint a provider's proofs may come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Endpoint {
+    pub method: &'static str,
+    /// Request path without the query string
+    pub path: &'static str,
+    /// Query parameters the request must carry exactly once, as `name=value`
+    pub query: &'static [&'static str],
+}
+
+/// The verified HTTP exchange of a transcript
+#[derive(Debug, Clone)]
+pub struct HttpExchange {
+    pub method: String,
+    /// Request target, including the query string
+    pub target: String,
+    /// Range of the response body within the received transcript
+    pub body: Range<usize>,
+}
+
+/// Maximum number of response headers
+const MAX_HEADERS: usize = 64;
+
+/// Parse the sent and received transcripts as HTTP and check that the request
+/// went to an allowed endpoint on `server_name` and got a 200 JSON response.
+///
+/// Unauthenticated bytes must already be replaced (e.g. with `X`), so that a
+/// redacted method, path, host or status can never match an expected value.
+/// `sent_authed[i]` tells whether `sent[i]` is authenticated: the request
+/// target must be, so a redacted query parameter can't override a required one.
+///
+/// The response head is parsed with httparse rather than spansy, since
+/// spansy parses a JSON body eagerly and a selectively disclosed body is not
+/// valid JSON. The body itself is left to `json::parse_disclosed`.
+pub fn verify_http_exchange(
+    sent: &[u8],
+    sent_authed: &[bool],
+    received: &[u8],
+    server_name: &str,
+    endpoints: &[Endpoint],
+) -> Result<HttpExchange, AttestationError> {
+    let request = parse_request(sent)
+        .map_err(|e| invalid(format!("Failed to parse HTTP request: {}", e)))?;
+
+    let method = span_str(request.request.method.span().as_bytes());
+    let target_span = request.request.target.span();
+    let target = span_str(target_span.as_bytes());
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+
+    let Some(endpoint) = endpoints
+        .iter()
+        .find(|e| e.method == method && e.path == path)
+    else {
+        return Err(invalid(format!(
+            "Endpoint {} {} is not allowed for {}",
+            method, path, server_name
+        )));
+    };
+
+    let target_authed = target_span
+        .indices()
+        .iter()
+        .all(|i| sent_authed.get(i).copied().unwrap_or(false));
+    if !target_authed {
+        return Err(invalid("HTTP request target is partially redacted".to_string()));
+    }
+
+    check_query(query, endpoint)?;
+
+    check_host(&request, server_name)?;
+
+    let body = check_response(received)?;
+
+    Ok(HttpExchange {
+        method,
+        target,
+        body,
+    })
+}
+
+/// Check the query string carries each of the endpoint's required parameters
+/// exactly once, with the required value
+fn check_query(query: &str, endpoint: &Endpoint) -> Result<(), AttestationError> {
+    for required in endpoint.query {
+        let name = required.split('=').next().unwrap_or_default();
+        let mut values = query
+            .split('&')
+            .filter(|param| param.split('=').next() == Some(name));
+
+        if values.next() != Some(*required) || values.next().is_some() {
+            return Err(invalid(format!(
+                "{} {} requires the query parameter {} exactly once",
+                endpoint.method, endpoint.path, required
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the request's Host header names the TLS server
+fn check_host(request: &Request, server_name: &str) -> Result<(), AttestationError> {
+    let mut hosts = request.headers_with_name("host");
+
+    let host = hosts
+        .next()
+        .map(|h| span_str(h.value.span().as_bytes()))
+        .ok_or_else(|| invalid("HTTP request has no Host header".to_string()))?;
+
+    if hosts.next().is_some() {
+        return Err(invalid("HTTP request has multiple Host headers".to_string()));
+    }
+
+    // Ignore an explicit default port
+    let host = host.trim();
+    let host = host.strip_suffix(":443").unwrap_or(host);
+
+    if !host.eq_ignore_ascii_case(server_name) {
+        return Err(invalid(format!(
+            "Host header {} does not match server {}",
+            host, server_name
+        )));
+    }
+
+    Ok(())
+}
+
+/// Check the response is a 200 with a JSON body and return the body's range
+fn check_response(received: &[u8]) -> Result<Range<usize>, AttestationError> {
+    let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+    let mut response = httparse::Response::new(&mut headers);
+
+    let head_len = match response.parse(received) {
+        Ok(httparse::Status::Complete(len)) => len,
+        Ok(httparse::Status::Partial) => {
+            return Err(invalid("HTTP response head is incomplete".to_string()))
+        }
+        Err(e) => return Err(invalid(format!("Failed to parse HTTP response: {}", e))),
+    };
+
+    if response.code != Some(200) {
+        return Err(invalid(format!(
+            "Expected HTTP status 200, got {}",
+            response.code.map(|c| c.to_string()).unwrap_or_default()
+        )));
+    }
+
+    check_json_content_type(response.headers)?;
+
+    if header_values(response.headers, "transfer-encoding").next().is_some() {
+        return Err(invalid("Transfer-encoded HTTP responses are not supported".to_string()));
+    }
+
+    let mut lengths = header_values(response.headers, "content-length");
+    let length = lengths
+        .next()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .ok_or_else(|| invalid("HTTP response has no valid Content-Length".to_string()))?;
+    if lengths.next().is_some() {
+        return Err(invalid("HTTP response has multiple Content-Length headers".to_string()));
+    }
+
+    let body = head_len..head_len + length;
+    if body.end > received.len() {
+        return Err(invalid(format!(
+            "HTTP response body ends at {} but the transcript has {} bytes",
+            body.end,
+            received.len()
+        )));
+    }
+
+    Ok(body)
+}
+
+/// Check the response declares a JSON body
+fn check_json_content_type(headers: &[httparse::Header]) -> Result<(), AttestationError> {
+    let content_type = header_values(headers, "content-type")
+        .next()
+        .ok_or_else(|| invalid("HTTP response has no Content-Type header".to_string()))?;
+
+    let media_type = content_type.split(';').next().unwrap_or_default().trim();
+
+    if !media_type.eq_ignore_ascii_case("application/json") {
+        return Err(invalid(format!(
+            "Expected application/json response, got {}",
+            content_type
+        )));
+    }
+
+    Ok(())
+}
+
+/// Values of the response headers named `name`
+fn header_values<'h>(
+    headers: &'h [httparse::Header<'h>],
+    name: &'h str,
+) -> impl Iterator<Item = String> + 'h {
+    headers
+        .iter()
+        .filter(move |h| h.name.eq_ignore_ascii_case(name))
+        .map(|h| span_str(h.value))
+}
+
+fn span_str(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn invalid(message: String) -> AttestationError {
+    AttestationError::InvalidHttpTranscript(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QONTO: &str = "thirdparty.qonto.com";
+    const ENDPOINTS: &[Endpoint] = &[Endpoint {
+        method: "GET",
+        path: "/v2/transactions",
+        query: &["per_page=1"],
+    }];
+
+    /// Check `sent`, fully authenticated
+    fn verify(sent: &[u8], received: &[u8]) -> Result<HttpExchange, AttestationError> {
+        verify_http_exchange(sent, &vec![true; sent.len()], received, QONTO, ENDPOINTS)
+    }
+
+    fn request(method: &str, target: &str, host: &str) -> Vec<u8> {
+        format!(
+            "{} {} HTTP/1.1\r\nhost: {}\r\naccept: application/json\r\nauthorization: XXXXXXXX\r\n\r\n",
+            method, target, host
+        )
+        .into_bytes()
+    }
+
+    fn response(status: &str, content_type: &str, body: &str) -> Vec<u8> {
+        format!(
+            "HTTP/1.1 {}\r\ncontent-type: {}\r\ncontent-length: {}\r\n\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            body
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn test_accepts_allowed_endpoint() {
+        let received = response("200 OK", "application/json; charset=utf-8", "{\"test\": \"value\"}");
+        let exchange = verify(
+            &request("GET", "/v2/transactions?slug=acme-1&per_page=1", QONTO),
+            &received,
+        )
+        .unwrap();
+
+        assert_eq!(exchange.method, "GET");
+        assert_eq!(exchange.target, "/v2/transactions?slug=acme-1&per_page=1");
+        assert_eq!(&received[exchange.body], b"{\"test\": \"value\"}");
+    }
+
+    #[test]
+    fn test_rejects_other_endpoints() {
+        for (method, target) in [
+            ("POST", "/v2/transactions"),
+            ("GET", "/v2/organization"),
+            ("GET", "/v2/transactionsX"),
+            ("GET", "/XXXXXXXXXXXXXXXX"),
+        ] {
+            let result = verify(
+                &request(method, target, QONTO),
+                &response("200 OK", "application/json", "{}"),
+            );
+            assert!(
+                matches!(result, Err(AttestationError::InvalidHttpTranscript(_))),
+                "{} {} should be rejected",
+                method,
+                target
+            );
+        }
+    }
+
+    #[test]
+    fn test_rejects_wrong_host() {
+        let result = verify(
+            &request("GET", "/v2/transactions?per_page=1", "evil.example.com"),
+            &response("200 OK", "application/json", "{}"),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_rejects_non_200_and_non_json() {
+        let result = verify(
+            &request("GET", "/v2/transactions?per_page=1", QONTO),
+            &response("404 Not Found", "application/json", "{}"),
+        );
+        assert!(result.is_err());
+
+        let result = verify(
+            &request("GET", "/v2/transactions?per_page=1", QONTO),
+            &response("200 OK", "text/html", "<html></html>"),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_requires_single_transaction_page() {
+        for target in [
+            "/v2/transactions",
+            "/v2/transactions?slug=acme-1",
+            "/v2/transactions?per_page=100",
+            "/v2/transactions?per_page=10",
+            "/v2/transactions?per_page=1&per_page=100",
+            "/v2/transactions?per_page=1XX",
+        ] {
+            let result = verify(
+                &request("GET", target, QONTO),
+                &response("200 OK", "application/json", "{}"),
+            );
+            assert!(result.is_err(), "{} should be rejected", target);
+        }
+    }
+
+    #[test]
+    fn test_rejects_partially_redacted_target() {
+        // The redacted run could hide `&per_page=100`
+        let sent = request("GET", "/v2/transactions?per_page=1&XXXXXXXXXXXX", QONTO);
+        let target = 4..4 + "/v2/transactions?per_page=1&XXXXXXXXXXXX".len();
+        let authed: Vec<bool> = (0..sent.len())
+            .map(|i| !target.contains(&i) || sent[i] != b'X')
+            .collect();
+
+        let result = verify_http_exchange(
+            &sent,
+            &authed,
+            &response("200 OK", "application/json", "{}"),
+            QONTO,
+            ENDPOINTS,
+        );
+        assert!(
+            matches!(result, Err(AttestationError::InvalidHttpTranscript(_))),
+            "{:?}",
+            result
+        );
+    }
+}
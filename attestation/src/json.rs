@@ -0,0 +1,336 @@
+//! Parsing of selectively disclosed JSON bodies
+//!
+//! A presentation may reveal only parts of a response body. The prover
+//! reveals the structure (braces, brackets, commas, keys) and the key/value
+//! pairs it wants to disclose; everything else is a run of unauthenticated
+//! bytes. This module reads such a body into a `serde_json::Value` that holds
+//! only values whose bytes are all authenticated, so fields can be looked up
+//! by their JSON path as usual.
+//!
+//! A redacted run is accepted only where a whole object member, array element
+//! or value fits. Revealed tokens must be fully authenticated and end at a
+//! revealed delimiter, so a redacted suffix can never truncate a number or
+//! string. Array elements after a redacted element are dropped, since the run
+//! may hide any number of elements and their indices can't be trusted.
+//!
+//! The verifier only learns which bytes are authenticated, not where the
+//! prover's hidden commitments start and end, so a run may hide more than one
+//! member. In `{"id":"A",XXXX,"amount_cents":1}` it could hide
+//! `"label":"x"},{"id":"B"`, splicing the id of one transaction with the
+//! amount of the next; likewise it could hide `"x":{` and `}` around nested
+//! members, making them read as top-level. This parser can't detect either.
+//! Providers rule out the first by binding the request to a single record
+//! (Qonto: `per_page=1`, checked in `http::verify_http_exchange`); the second
+//! needs a nested object whose members share names with the fields read.
+
+use serde_json::{Map, Number, Value};
+
+use crate::error::AttestationError;
+
+/// Maximum nesting depth of a disclosed body
+const MAX_DEPTH: usize = 64;
+
+/// Parse a selectively disclosed JSON body.
+///
+/// `authed[i]` tells whether `body[i]` is authenticated. Unauthenticated
+/// bytes are never interpreted.
+pub fn parse_disclosed(body: &[u8], authed: &[bool]) -> Result<Value, AttestationError> {
+    if body.len() != authed.len() {
+        return Err(AttestationError::Internal(
+            "body and authentication mask lengths differ".to_string(),
+        ));
+    }
+
+    let mut parser = Parser { body, authed, pos: 0 };
+
+    let value = match parser.value(0)? {
+        Parsed::Value(value) => value,
+        Parsed::Redacted => return Err(parser.error("response body is fully redacted")),
+    };
+
+    parser.skip_whitespace();
+    if parser.pos != body.len() {
+        return Err(parser.error("unexpected content after JSON value"));
+    }
+
+    Ok(value)
+}
+
+/// A parsed value, or a redacted run in its place
+enum Parsed {
+    Value(Value),
+    Redacted,
+}
+
+struct Parser<'a> {
+    body: &'a [u8],
+    authed: &'a [bool],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    /// The authenticated byte at the current position
+    fn peek(&self) -> Option<u8> {
+        (self.pos < self.body.len() && self.authed[self.pos]).then(|| self.body[self.pos])
+    }
+
+    /// Skip a run of unauthenticated bytes, returning whether there was one
+    fn skip_redacted(&mut self) -> bool {
+        let start = self.pos;
+        while self.pos < self.body.len() && !self.authed[self.pos] {
+            self.pos += 1;
+        }
+        self.pos > start
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), AttestationError> {
+        if self.peek() != Some(byte) {
+            return Err(self.error(&format!("expected '{}'", byte as char)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Parsed, AttestationError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("JSON nesting too deep"));
+        }
+
+        self.skip_whitespace();
+        if self.skip_redacted() {
+            return Ok(Parsed::Redacted);
+        }
+
+        let value = match self.peek() {
+            Some(b'{') => self.object(depth)?,
+            Some(b'[') => self.array(depth)?,
+            Some(b'"') => Value::String(self.string()?),
+            Some(b'-' | b'0'..=b'9') => self.number()?,
+            Some(b't') => self.literal("true", Value::Bool(true))?,
+            Some(b'f') => self.literal("false", Value::Bool(false))?,
+            Some(b'n') => self.literal("null", Value::Null)?,
+            _ => return Err(self.error("expected a JSON value")),
+        };
+
+        Ok(Parsed::Value(value))
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Value, AttestationError> {
+        self.expect(b'{')?;
+        let mut map = Map::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Value::Object(map));
+        }
+
+        loop {
+            self.skip_whitespace();
+
+            // A redacted run in member position hides whole members
+            if !self.skip_redacted() {
+                let key = self.string()?;
+                self.skip_whitespace();
+                self.expect(b':')?;
+
+                if let Parsed::Value(value) = self.value(depth + 1)? {
+                    if map.insert(key.clone(), value).is_some() {
+                        return Err(self.error(&format!("duplicate key {}", key)));
+                    }
+                }
+            }
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(map));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Value, AttestationError> {
+        self.expect(b'[')?;
+        let mut elements = Vec::new();
+        let mut truncated = false;
+
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Value::Array(elements));
+        }
+
+        loop {
+            match self.value(depth + 1)? {
+                Parsed::Value(value) if !truncated => elements.push(value),
+                Parsed::Value(_) => {}
+                Parsed::Redacted => truncated = true,
+            }
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Value::Array(elements));
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, AttestationError> {
+        let start = self.pos;
+        self.expect(b'"')?;
+
+        loop {
+            match self.peek() {
+                Some(b'"') => break,
+                Some(b'\\') => self.pos += 2,
+                Some(_) => self.pos += 1,
+                None => return Err(self.error("unterminated or partially redacted string")),
+            }
+        }
+        self.pos += 1;
+
+        if self.authed[start..self.pos].contains(&false) {
+            return Err(self.error("partially redacted string"));
+        }
+
+        serde_json::from_slice(&self.body[start..self.pos])
+            .map_err(|e| self.error(&format!("invalid string: {}", e)))
+    }
+
+    fn number(&mut self) -> Result<Value, AttestationError> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')) {
+            self.pos += 1;
+        }
+
+        serde_json::from_slice::<Number>(&self.body[start..self.pos])
+            .map(Value::Number)
+            .map_err(|e| self.error(&format!("invalid number: {}", e)))
+    }
+
+    fn literal(&mut self, text: &str, value: Value) -> Result<Value, AttestationError> {
+        for &byte in text.as_bytes() {
+            self.expect(byte)?;
+        }
+        Ok(value)
+    }
+
+    fn error(&self, message: &str) -> AttestationError {
+        AttestationError::InvalidPaymentData(format!(
+            "Malformed disclosed JSON at byte {}: {}",
+            self.pos, message
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Build a body and mask from a template where `X` marks redacted bytes
+    fn disclosed(template: &str) -> (Vec<u8>, Vec<bool>) {
+        let authed = template.bytes().map(|b| b != b'X').collect();
+        (template.as_bytes().to_vec(), authed)
+    }
+
+    fn parse(template: &str) -> Result<Value, AttestationError> {
+        let (body, authed) = disclosed(template);
+        parse_disclosed(&body, &authed)
+    }
+
+    #[test]
+    fn test_fully_revealed_body() {
+        let text = r#"{"transactions": [{"id": "tx-1", "amount_cents": 10000, "ok": true, "note": null, "label": "A \"B\""}], "meta": {}}"#;
+        let value = parse(text).unwrap();
+        assert_eq!(value, serde_json::from_str::<Value>(text).unwrap());
+    }
+
+    #[test]
+    fn test_redacted_members_and_values() {
+        let value = parse(
+            r#"{"transactions":[{XXXXXXXXXXXXXXXXXXXXXXXX,"amount_cents":10000,"settled_balance":XXXXXX,"transfer":{"counterparty_account_number":"DE89370400440532013000",XXXXXXXXXXXXXX}}],XXXXXXXXXXXXXXXX}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            value,
+            json!({
+                "transactions": [{
+                    "amount_cents": 10000,
+                    "transfer": { "counterparty_account_number": "DE89370400440532013000" }
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_only_required_fields_revealed() {
+        let value = parse(concat!(
+            r#"{"transactions":[{"id":"tx-1",XXXXXXXXXXXXXXXXXXXXXXXXXXX,"amount_cents":10000,"#,
+            r#""currency":"EUR",XXXXXXXXXXXXXXXX,"status":"completed","side":"debit","#,
+            r#""reference":"FF-1",XXXXXXXXXXXXX,"transfer":{"counterparty_account_number":"DE89","#,
+            r#"XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX}}],XXXXXXXXXXXXXXXXXXXXXXXX}"#,
+        ))
+        .unwrap();
+
+        assert_eq!(
+            value,
+            json!({
+                "transactions": [{
+                    "id": "tx-1",
+                    "amount_cents": 10000,
+                    "currency": "EUR",
+                    "status": "completed",
+                    "side": "debit",
+                    "reference": "FF-1",
+                    "transfer": { "counterparty_account_number": "DE89" }
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_rejects_partially_redacted_tokens() {
+        // Truncated number
+        assert!(parse(r#"{"amount_cents":100XX}"#).is_err());
+        // Partially redacted string
+        assert!(parse(r#"{"id":"tx-XXX"}"#).is_err());
+        // Partially redacted key
+        assert!(parse(r#"{"amountXXXXXX":10000}"#).is_err());
+        // Redacted run inside a member
+        assert!(parse(r#"{XXXXXX"amount_cents":10000}"#).is_err());
+        // Trailing redacted content
+        assert!(parse(r#"{"amount_cents":10000}XXXX"#).is_err());
+        // Nothing revealed
+        assert!(parse("XXXXXXXX").is_err());
+    }
+
+    #[test]
+    fn test_drops_elements_after_redacted_element() {
+        let value = parse(r#"{"transactions":[{"id":"a"},XXXXXXXXXX,{"id":"c"}]}"#).unwrap();
+        assert_eq!(value, json!({ "transactions": [{ "id": "a" }] }));
+
+        let value = parse(r#"{"transactions":[XXXXXXXXXX,{"id":"c"}]}"#).unwrap();
+        assert_eq!(value, json!({ "transactions": [] }));
+    }
+
+    #[test]
+    fn test_rejects_duplicate_keys() {
+        assert!(parse(r#"{"amount_cents":1,XXXXX,"amount_cents":2}"#).is_err());
+    }
+}
@@ -0,0 +1,26 @@
+//! Attestation service: verifies TLSNotary presentations and signs EIP-712
+//! attestations for on-chain verification.
+
+pub mod amount;
+pub mod api;
+pub mod attestation;
+pub mod audit;
+pub mod auth;
+pub mod chain;
+pub mod cluster;
+pub mod config;
+pub mod eip712;
+pub mod error;
+pub mod http;
+pub mod json;
+pub mod providers;
+pub mod reference;
+pub mod self_check;
+pub mod server_policy;
+pub mod signer;
+pub mod store;
+pub mod typed_data;
+pub mod verification;
+pub mod witness;
+
+pub use config::Config;
@@ -1,22 +1,10 @@
-mod api;
-mod attestation;
-mod audit;
-mod auth;
-mod chain;
-mod config;
-mod eip712;
-mod error;
-mod verification;
-
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
-use axum::{routing::post, Router};
-use tower_http::cors::CorsLayer;
-use tower_http::trace::TraceLayer;
 use tracing::info;
 
-pub use config::Config;
+use attestation_service::{api, Config};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -30,18 +18,26 @@ async fn main() -> anyhow::Result<()> {
     let config = Config::from_env()?;
 
     info!("Starting Attestation Service");
-    info!("  Witness address: 0x{}", hex::encode(config.witness_address()));
 
     // Create app state
     let state = Arc::new(api::AppState::new(config)?);
 
+    // Make sure the deployed verifier would accept our signatures
+    state.startup_self_check().await?;
+
+    // Keep the self-check, and with it witness key rotation, up to date
+    let refresher = state.clone();
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(api::SELF_CHECK_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            refresher.current_self_check().await;
+        }
+    });
+
     // Build routes
-    let app = Router::new()
-        .route("/api/v1/attest", post(api::attest))
-        .route("/api/v1/health", axum::routing::get(api::health))
-        .layer(CorsLayer::permissive())
-        .layer(TraceLayer::new_for_http())
-        .with_state(state);
+    let app = api::router(state);
 
     // Start server
     let port: u16 = std::env::var("PORT")
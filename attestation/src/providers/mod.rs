@@ -0,0 +1,188 @@
+//! Bank providers whose APIs payments can be proven against
+//!
+//! Each provider knows the TLS server its API is served from, which HTTP
+//! endpoints a proof may come from, how to read payment fields from the
+//! disclosed response and which transaction status counts as a payment.
+//! Adding a bank means adding a module here and listing it in `known_providers`.
+
+mod qonto;
+
+use std::sync::Arc;
+
+pub use qonto::Qonto;
+
+use crate::error::AttestationError;
+use crate::http::Endpoint;
+use crate::server_policy::ServerPolicyEntry;
+
+/// Transaction status a provider must report before a payment is attested
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusPolicy {
+    /// Required settled status (e.g. "completed")
+    pub status: &'static str,
+
+    /// Required transaction side for an outgoing payment (e.g. "debit")
+    pub side: &'static str,
+}
+
+/// Payment fields read from a disclosed response body
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PaymentFields {
+    pub transaction_id: Option<String>,
+    pub amount_cents: Option<i64>,
+    /// ISO 4217 code, uppercase
+    pub currency: Option<String>,
+    pub beneficiary_iban: Option<String>,
+    pub beneficiary_name: Option<String>,
+    pub status: Option<String>,
+    pub side: Option<String>,
+    /// Transfer reference (remittance information)
+    pub reference: Option<String>,
+    /// When the bank executed the transfer (unix seconds)
+    pub executed_at: Option<u64>,
+}
+
+/// A bank whose API responses can be attested
+pub trait PaymentProvider: Send + Sync {
+    /// Short identifier, e.g. "qonto"
+    fn name(&self) -> &'static str;
+
+    /// TLS server name of the provider's API
+    fn server_name(&self) -> &'static str;
+
+    /// HTTP endpoints a proof may come from
+    fn endpoints(&self) -> &'static [Endpoint];
+
+    /// Status a transaction must have to count as a completed payment
+    fn status_policy(&self) -> StatusPolicy;
+
+    /// Read the payment fields from a disclosed response body.
+    ///
+    /// Only revealed fields are present in `body`; a redacted field is
+    /// reported as missing. Inconsistent fields are an error.
+    fn parse_payment(&self, body: &serde_json::Value) -> Result<PaymentFields, AttestationError>;
+}
+
+/// All providers this service supports
+fn known_providers() -> Vec<Arc<dyn PaymentProvider>> {
+    vec![Arc::new(Qonto)]
+}
+
+/// The providers enabled for this deployment, resolved from the server policy
+#[derive(Clone)]
+pub struct ProviderRegistry {
+    entries: Vec<(ServerPolicyEntry, Arc<dyn PaymentProvider>)>,
+}
+
+impl ProviderRegistry {
+    /// Resolve each policy entry to a provider: the bound provider if the
+    /// entry names one, otherwise the single known provider whose server
+    /// name the entry matches.
+    pub fn from_policy(policy: &[ServerPolicyEntry]) -> Result<Self, String> {
+        let known = known_providers();
+
+        let entries = policy
+            .iter()
+            .map(|entry| {
+                let provider = match &entry.provider {
+                    Some(name) => known
+                        .iter()
+                        .find(|p| p.name() == name)
+                        .ok_or_else(|| format!("Unknown payment provider {} in {}", name, entry))?,
+                    None => {
+                        let mut matching = known.iter().filter(|p| entry.matches(p.server_name()));
+                        match (matching.next(), matching.next()) {
+                            (Some(provider), None) => provider,
+                            (None, _) => {
+                                return Err(format!("No payment provider for server {}", entry))
+                            }
+                            (Some(_), Some(_)) => {
+                                return Err(format!(
+                                    "Several payment providers match {}; bind one with {}=<provider>",
+                                    entry, entry
+                                ))
+                            }
+                        }
+                    }
+                };
+                Ok((entry.clone(), provider.clone()))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Self { entries })
+    }
+
+    /// Find the provider for a TLS server name, from the first policy entry
+    /// it matches. A rejection lists the entries the name failed to match.
+    pub fn resolve(&self, server_name: &str) -> Result<&dyn PaymentProvider, AttestationError> {
+        self.entries
+            .iter()
+            .find(|(entry, _)| entry.matches(server_name))
+            .map(|(_, provider)| provider.as_ref())
+            .ok_or_else(|| AttestationError::UnexpectedServer {
+                expected: self
+                    .entries
+                    .iter()
+                    .map(|(entry, _)| entry.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                actual: server_name.to_string(),
+            })
+    }
+
+    /// Look up the provider for a TLS server name
+    pub fn get(&self, server_name: &str) -> Option<&dyn PaymentProvider> {
+        self.resolve(server_name).ok()
+    }
+
+    /// Policy entries with the name of the provider each resolved to
+    pub fn describe(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|(entry, provider)| format!("{} -> {}", entry, provider.name()))
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for ProviderRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.describe()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::server_policy::parse_server_policy;
+
+    fn build(policy: &str) -> Result<ProviderRegistry, String> {
+        ProviderRegistry::from_policy(&parse_server_policy(policy).unwrap())
+    }
+
+    #[test]
+    fn test_registry_resolves_providers() {
+        let registry = build("thirdparty.qonto.com").unwrap();
+        assert_eq!(registry.resolve("thirdparty.qonto.com").unwrap().name(), "qonto");
+        assert!(registry.get("api.example.com").is_none());
+
+        let registry = build("*.qonto.com=qonto").unwrap();
+        assert_eq!(registry.resolve("sandbox.qonto.com").unwrap().name(), "qonto");
+
+        assert!(build("api.example.com").is_err());
+        assert!(build("api.example.com=unknown").is_err());
+    }
+
+    #[test]
+    fn test_rejection_reports_policy() {
+        let registry = build("thirdparty.qonto.com, *.example.com=qonto").unwrap();
+
+        match registry.resolve("thirdparty.qonto.com.attacker.net") {
+            Err(AttestationError::UnexpectedServer { expected, actual }) => {
+                assert_eq!(expected, "thirdparty.qonto.com, *.example.com=qonto");
+                assert_eq!(actual, "thirdparty.qonto.com.attacker.net");
+            }
+            _ => panic!("expected UnexpectedServer"),
+        }
+    }
+}
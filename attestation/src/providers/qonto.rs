@@ -0,0 +1,299 @@
+//! Qonto business banking API
+
+use serde_json::Value;
+
+use super::{PaymentFields, PaymentProvider, StatusPolicy};
+use crate::amount::{json_decimal, json_minor_units};
+use crate::chain::Currency;
+use crate::error::AttestationError;
+use crate::http::Endpoint;
+
+/// Qonto: proofs come from the transactions listing
+/// (`GET /v2/transactions?...&per_page=1`). With one transaction per page a
+/// redacted run can't splice fields of two transactions together.
+pub struct Qonto;
+
+impl PaymentProvider for Qonto {
+    fn name(&self) -> &'static str {
+        "qonto"
+    }
+
+    fn server_name(&self) -> &'static str {
+        "thirdparty.qonto.com"
+    }
+
+    fn endpoints(&self) -> &'static [Endpoint] {
+        &[Endpoint {
+            method: "GET",
+            path: "/v2/transactions",
+            query: &["per_page=1"],
+        }]
+    }
+
+    /// Only completed outgoing transactions are payments
+    fn status_policy(&self) -> StatusPolicy {
+        StatusPolicy {
+            status: "completed",
+            side: "debit",
+        }
+    }
+
+    fn parse_payment(&self, body: &Value) -> Result<PaymentFields, AttestationError> {
+        let tx = body.get("transactions").and_then(|t| t.get(0));
+        let str_field =
+            |value: Option<&Value>| value.and_then(|v| v.as_str()).map(|s| s.to_string());
+
+        // Amount in the account currency, or in the local currency as a
+        // fallback; the currency always comes from the same pair as the amount
+        let (amount_cents, currency) = match amount(tx, "amount", "amount_cents", "currency")? {
+            Some(amount) => amount,
+            None => amount(tx, "local_amount", "local_amount_cents", "local_currency")?
+                .unwrap_or_default(),
+        };
+        let amount_cents = amount_cents.filter(|&amount| amount != 0);
+
+        Ok(PaymentFields {
+            transaction_id: str_field(tx.and_then(|t| t.get("id"))),
+            amount_cents,
+            currency,
+            // SEPA transfers carry the beneficiary IBAN in the transfer details
+            beneficiary_iban: str_field(
+                tx.and_then(|t| t.get("transfer"))
+                    .and_then(|t| t.get("counterparty_account_number")),
+            ),
+            // Qonto puts the counterparty name in the transaction label
+            beneficiary_name: str_field(tx.and_then(|t| t.get("label"))),
+            status: str_field(tx.and_then(|t| t.get("status"))),
+            side: str_field(tx.and_then(|t| t.get("side"))),
+            reference: str_field(tx.and_then(|t| t.get("reference"))),
+            executed_at: settled_at(tx)?,
+        })
+    }
+}
+
+/// Settlement time of a transaction, given by Qonto in RFC 3339
+fn settled_at(tx: Option<&Value>) -> Result<Option<u64>, AttestationError> {
+    let Some(value) = tx.and_then(|t| t.get("settled_at")).and_then(|v| v.as_str()) else {
+        return Ok(None);
+    };
+
+    chrono::DateTime::parse_from_rfc3339(value)
+        .ok()
+        .and_then(|t| u64::try_from(t.timestamp()).ok())
+        .map(Some)
+        .ok_or_else(|| AttestationError::InvalidPaymentData(format!("settled_at: invalid time {}", value)))
+}
+
+/// An amount in minor units and its currency code, either possibly absent
+type Amount = (Option<i64>, Option<String>);
+
+/// Read an amount given as a decimal (`amount`), in minor units
+/// (`amount_cents`), or both, in which case they must agree. The decimal is
+/// checked against the minor unit of its currency.
+///
+/// Returns the amount and its currency code, or `None` if neither amount
+/// field is present.
+fn amount(
+    tx: Option<&Value>,
+    decimal_key: &str,
+    minor_key: &str,
+    currency_key: &str,
+) -> Result<Option<Amount>, AttestationError> {
+    let field = |key: &str| tx.and_then(|t| t.get(key)).filter(|v| !v.is_null());
+    let invalid =
+        |key: &str, e: String| AttestationError::InvalidPaymentData(format!("{}: {}", key, e));
+
+    let minor = field(minor_key)
+        .map(json_minor_units)
+        .transpose()
+        .map_err(|e| invalid(minor_key, e))?;
+
+    let currency_code = field(currency_key)
+        .and_then(|v| v.as_str())
+        .map(|code| code.trim().to_ascii_uppercase());
+
+    let decimal = match field(decimal_key) {
+        Some(value) => {
+            let currency = currency_code
+                .as_deref()
+                .and_then(Currency::from_code)
+                .ok_or_else(|| invalid(decimal_key, format!("no supported {}", currency_key)))?;
+
+            Some(
+                json_decimal(value, currency.minor_unit_exponent())
+                    .map_err(|e| invalid(decimal_key, e))?,
+            )
+        }
+        None => None,
+    };
+
+    match (minor, decimal) {
+        (Some(minor), Some(decimal)) if minor != decimal => {
+            Err(AttestationError::InvalidPaymentData(format!(
+                "{} ({}) and {} ({}) disagree",
+                decimal_key, decimal, minor_key, minor
+            )))
+        }
+        (None, None) => Ok(None),
+        (minor, decimal) => Ok(Some((minor.or(decimal), currency_code))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::parse_disclosed;
+
+    fn expected() -> PaymentFields {
+        PaymentFields {
+            transaction_id: Some("tx-123".to_string()),
+            amount_cents: Some(10000),
+            currency: Some("EUR".to_string()),
+            beneficiary_iban: Some("DE89370400440532013000".to_string()),
+            beneficiary_name: Some("Max Mustermann".to_string()),
+            status: Some("completed".to_string()),
+            side: Some("debit".to_string()),
+            reference: Some("FF-00000000000000000".to_string()),
+            executed_at: Some(1735725600),
+        }
+    }
+
+    #[test]
+    fn test_parse_qonto_transaction() {
+        let json = r#"{
+            "transactions": [{
+                "id": "tx-123",
+                "amount_cents": 10000,
+                "currency": "EUR",
+                "status": "completed",
+                "side": "debit",
+                "reference": "FF-00000000000000000",
+                "settled_at": "2025-01-01T10:00:00.000Z",
+                "label": "Max Mustermann",
+                "transfer": {
+                    "counterparty_account_number": "DE89370400440532013000"
+                }
+            }],
+            "meta": { "current_page": 1 }
+        }"#;
+
+        let fields = Qonto
+            .parse_payment(&serde_json::from_str(json).unwrap())
+            .unwrap();
+        assert_eq!(fields, expected());
+    }
+
+    #[test]
+    fn test_parse_redacted_qonto_transaction() {
+        // Only the fields the service needs are revealed; `X` marks redacted bytes
+        let body = concat!(
+            r#"{"transactions":[{"id":"tx-123","amount_cents":10000,"currency":"EUR","#,
+            r#"XXXXXXXXXXXXXXXXXXXXXXXX,"status":"completed","side":"debit","#,
+            r#""reference":"FF-00000000000000000","settled_at":"2025-01-01T10:00:00.000Z","#,
+            r#""label":"Max Mustermann","transfer":{XXXXXXXXXXXXXXXX,"#,
+            r#""counterparty_account_number":"DE89370400440532013000"}}],"#,
+            r#"XXXXXXXXXXXXXXXXXXXX}"#,
+        );
+        let authed: Vec<bool> = body.bytes().map(|b| b != b'X').collect();
+
+        let value = parse_disclosed(body.as_bytes(), &authed).unwrap();
+        assert_eq!(Qonto.parse_payment(&value).unwrap(), expected());
+    }
+
+    #[test]
+    fn test_ignores_non_qonto_shapes() {
+        let json = r#"{
+            "transaction": { "id": "tx-123", "amount_cents": 10000 },
+            "transactions": [{ "counterparty": { "iban": "DE89370400440532013000" } }]
+        }"#;
+
+        let fields = Qonto
+            .parse_payment(&serde_json::from_str(json).unwrap())
+            .unwrap();
+        assert_eq!(fields, PaymentFields::default());
+    }
+
+    fn parse_amount(tx: serde_json::Value) -> Result<Option<i64>, AttestationError> {
+        Qonto
+            .parse_payment(&serde_json::json!({ "transactions": [tx] }))
+            .map(|fields| fields.amount_cents)
+    }
+
+    #[test]
+    fn test_decimal_amounts() {
+        use serde_json::json;
+
+        assert_eq!(
+            parse_amount(json!({ "amount": 19.99, "currency": "EUR" })).unwrap(),
+            Some(1999)
+        );
+        assert_eq!(
+            parse_amount(json!({ "amount": "19.99", "currency": "EUR" })).unwrap(),
+            Some(1999)
+        );
+        assert_eq!(
+            parse_amount(json!({ "amount": 19.99, "amount_cents": 1999, "currency": "EUR" }))
+                .unwrap(),
+            Some(1999)
+        );
+        assert_eq!(
+            parse_amount(
+                json!({ "local_amount": 5.5, "local_currency": "GBP", "local_amount_cents": null })
+            )
+            .unwrap(),
+            Some(550)
+        );
+
+        // amount and amount_cents disagree
+        assert!(
+            parse_amount(json!({ "amount": 19.99, "amount_cents": 1998, "currency": "EUR" }))
+                .is_err()
+        );
+        // More decimal places than the currency has
+        assert!(parse_amount(json!({ "amount": 19.999, "currency": "EUR" })).is_err());
+        // A decimal amount needs a supported currency
+        assert!(parse_amount(json!({ "amount": 19.99 })).is_err());
+        assert!(parse_amount(json!({ "amount": 1999, "currency": "JPY" })).is_err());
+    }
+
+    #[test]
+    fn test_currency_follows_amount() {
+        use serde_json::json;
+
+        let parse = |tx: serde_json::Value| {
+            let fields = Qonto
+                .parse_payment(&json!({ "transactions": [tx] }))
+                .unwrap();
+            (fields.amount_cents, fields.currency)
+        };
+
+        assert_eq!(
+            parse(json!({ "amount_cents": 10000, "currency": "eur" })),
+            (Some(10000), Some("EUR".to_string()))
+        );
+        assert_eq!(
+            parse(json!({ "local_amount_cents": 8500, "local_currency": "GBP" })),
+            (Some(8500), Some("GBP".to_string()))
+        );
+        // Redacted currency
+        assert_eq!(parse(json!({ "amount_cents": 10000 })), (Some(10000), None));
+    }
+
+    #[test]
+    fn test_settlement_time() {
+        use serde_json::json;
+
+        let parse = |tx: serde_json::Value| {
+            Qonto
+                .parse_payment(&json!({ "transactions": [tx] }))
+                .map(|fields| fields.executed_at)
+        };
+
+        assert_eq!(
+            parse(json!({ "settled_at": "2025-01-01T11:00:00+01:00" })).unwrap(),
+            Some(1735725600)
+        );
+        assert_eq!(parse(json!({ "settled_at": null })).unwrap(), None);
+        assert!(parse(json!({ "settled_at": "yesterday" })).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+//! Intent-bound payment references
+//!
+//! A solver sends each transfer with a reference derived from the intent hash,
+//! and the attestation service only attests a payment whose proven reference
+//! carries that code. A bank transfer therefore can't be reused for another
+//! intent with the same amount and beneficiary.
+//!
+//! The code is `FF-` followed by the first 80 bits of the intent hash in
+//! Crockford base32 (16 characters) and a Luhn mod 32 check character, e.g.
+//! `FF-1CR5AYMZRKMGWCTRR`. It only uses letters and digits allowed in SEPA
+//! remittance information.
+
+use crate::error::AttestationError;
+
+/// Prefix of every payment reference
+pub const REFERENCE_PREFIX: &str = "FF";
+
+/// Crockford base32 alphabet (no I, L, O, U)
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Number of intent hash bits encoded in the reference
+const CODE_BITS: usize = 80;
+
+/// Payment reference a solver must use for the transfer filling an intent
+pub fn payment_reference(intent_hash: &[u8; 32]) -> String {
+    let code = encode_code(intent_hash);
+    format!(
+        "{}-{}{}",
+        REFERENCE_PREFIX,
+        code,
+        check_character(&code) as char
+    )
+}
+
+/// Check that a proven transfer reference carries the intent's code.
+///
+/// Banks may add text around the reference or drop separators, so the
+/// reference is compared on uppercase letters and digits only and must
+/// contain the whole code, check character included.
+pub fn check_payment_reference(
+    reference: Option<&str>,
+    intent_hash: &[u8; 32],
+) -> Result<(), AttestationError> {
+    let expected = payment_reference(intent_hash);
+
+    let reference = reference.ok_or_else(|| {
+        AttestationError::InvalidPaymentReference(format!(
+            "proof does not contain a transfer reference, expected {}",
+            expected
+        ))
+    })?;
+
+    if !normalize(reference).contains(&normalize(&expected)) {
+        return Err(AttestationError::InvalidPaymentReference(format!(
+            "expected {}, got {}",
+            expected, reference
+        )));
+    }
+
+    Ok(())
+}
+
+/// Crockford base32 of the leading `CODE_BITS` bits of the intent hash
+fn encode_code(intent_hash: &[u8; 32]) -> String {
+    let bits = intent_hash[..CODE_BITS / 8]
+        .iter()
+        .fold(0u128, |acc, &b| (acc << 8) | b as u128);
+
+    (0..CODE_BITS / 5)
+        .rev()
+        .map(|i| ALPHABET[((bits >> (i * 5)) & 0x1f) as usize] as char)
+        .collect()
+}
+
+/// Luhn mod 32 check character over base32 text
+fn check_character(code: &str) -> u8 {
+    let n = ALPHABET.len();
+    let sum: usize = code
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(i, c)| {
+            let value = ALPHABET.iter().position(|&a| a == c).unwrap_or(0);
+            let addend = if i % 2 == 0 { value * 2 } else { value };
+            addend / n + addend % n
+        })
+        .sum();
+
+    ALPHABET[(n - sum % n) % n]
+}
+
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn intent_hash() -> [u8; 32] {
+        let mut hash = [0u8; 32];
+        for (i, b) in hash.iter_mut().enumerate() {
+            *b = (i as u8).wrapping_mul(37).wrapping_add(11);
+        }
+        hash
+    }
+
+    #[test]
+    fn test_payment_reference_format() {
+        assert_eq!(payment_reference(&[0u8; 32]), "FF-00000000000000000");
+        assert_eq!(payment_reference(&[0xff; 32]), "FF-ZZZZZZZZZZZZZZZZG");
+
+        let reference = payment_reference(&intent_hash());
+        assert_eq!(reference, "FF-1CR5AYMZRKMGWCTRR");
+
+        // Only the leading 80 bits are encoded
+        let mut other = intent_hash();
+        other[31] ^= 1;
+        assert_eq!(payment_reference(&other), reference);
+        other[0] ^= 1;
+        assert_ne!(payment_reference(&other), reference);
+
+        // The check character detects a single substituted character
+        let code = encode_code(&intent_hash());
+        let mut altered = code.clone().into_bytes();
+        altered[5] = if altered[5] == b'A' { b'B' } else { b'A' };
+        assert_ne!(
+            check_character(&code),
+            check_character(std::str::from_utf8(&altered).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_check_payment_reference() {
+        let hash = intent_hash();
+        let reference = payment_reference(&hash);
+
+        assert!(check_payment_reference(Some(&reference), &hash).is_ok());
+        assert!(check_payment_reference(Some(&reference.to_lowercase()), &hash).is_ok());
+        assert!(check_payment_reference(Some(&reference.replace('-', " ")), &hash).is_ok());
+        assert!(check_payment_reference(Some(&format!("SEPA {} /ROC", reference)), &hash).is_ok());
+
+        assert!(check_payment_reference(None, &hash).is_err());
+        assert!(check_payment_reference(Some("OFFRAMP-12345678"), &hash).is_err());
+        assert!(check_payment_reference(Some(&reference[..reference.len() - 1]), &hash).is_err());
+
+        let mut other = hash;
+        other[0] ^= 1;
+        assert!(check_payment_reference(Some(&reference), &other).is_err());
+    }
+}
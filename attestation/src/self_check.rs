@@ -0,0 +1,197 @@
+//! Self-check against the deployed PaymentVerifier
+//!
+//! A signature is only worth something if the verifier accepts it: the
+//! contract must trust the witness address, and it must hash attestations
+//! exactly like `eip712` does. The service asks the verifier whether
+//! `authorizedWitnesses(witness)` is set for each witness key, for its
+//! `DOMAIN_SEPARATOR` and for its `getDigest` of a fixed sample attestation,
+//! and compares the answers with the local values. Its `minWitnesses` must
+//! not exceed the signatures the service collects. It runs at startup and is
+//! refreshed periodically.
+
+use alloy_primitives::{keccak256, Address, B256, U256};
+use serde::Serialize;
+
+use crate::audit::current_timestamp;
+use crate::chain::{ChainClient, PaymentAttestation};
+use crate::eip712::{attestation_digest, AttestationData, AttestationDomain};
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    /// The verifier agrees with the local configuration
+    Passed,
+    /// The verifier disagrees: its signatures would be rejected on-chain
+    Failed,
+    /// The verifier could not be queried
+    Unavailable,
+}
+
+/// One check of the self-check
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    /// Witness address, for a `witness_authorized` check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub witness: Option<String>,
+    pub status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Result of a self-check run
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfCheckReport {
+    pub verifier: String,
+    pub checked_at: u64,
+    pub checks: Vec<Check>,
+    /// Whether the verifier trusts each witness (`None` if the call failed)
+    #[serde(skip)]
+    pub authorized: Vec<(Address, Option<bool>)>,
+}
+
+impl SelfCheckReport {
+    /// Whether every check passed
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|c| c.status == CheckStatus::Passed)
+    }
+
+    /// Checks where the verifier disagrees with the local configuration
+    pub fn mismatches(&self) -> impl Iterator<Item = &Check> {
+        self.checks
+            .iter()
+            .filter(|c| c.status == CheckStatus::Failed)
+    }
+
+    /// Whether the verifier agrees with everything but the individual
+    /// witness keys: it hashes attestations like the service does and asks
+    /// for no more signatures than it collects
+    pub fn configuration_matches(&self) -> bool {
+        self.mismatches().all(|c| c.witness.is_some())
+    }
+}
+
+/// Fixed attestation whose digest is compared with the verifier's
+pub fn sample_attestation() -> AttestationData {
+    AttestationData {
+        intent_hash: keccak256(b"freeflo-self-check").0,
+        amount: 10000,
+        timestamp: 1703500000,
+        payment_id: "self-check".to_string(),
+        data: b"self-check".to_vec(),
+    }
+}
+
+/// Compare the verifier with the local witness addresses, EIP-712 domain and
+/// number of witness signatures collected per attestation.
+///
+/// Returns `None` without a verifier contract, since there is nothing to
+/// compare with.
+pub async fn run_self_check(
+    chain: &ChainClient,
+    witnesses: &[Address],
+    domain: &AttestationDomain,
+    min_witnesses: usize,
+) -> Option<SelfCheckReport> {
+    let verifier = chain.verifier_contract()?;
+
+    let mut checks = Vec::new();
+    let mut authorized = Vec::new();
+    for &witness in witnesses {
+        let result = chain.is_witness_authorized(witness).await;
+        let check = match &result {
+            Ok(Some(true)) => passed("witness_authorized"),
+            Ok(_) => failed(
+                "witness_authorized",
+                format!("witness {} is not an authorized witness", witness),
+            ),
+            Err(e) => unavailable("witness_authorized", e.clone()),
+        };
+        checks.push(Check {
+            witness: Some(format!("{}", witness)),
+            ..check
+        });
+        authorized.push((witness, result.ok().flatten()));
+    }
+
+    let local_separator = domain.domain_separator();
+    let domain_separator = match chain.verifier_domain_separator().await {
+        Ok(Some(separator)) => compare("domain_separator", local_separator, separator),
+        Ok(None) => unavailable("domain_separator", "no verifier contract".to_string()),
+        Err(e) => unavailable("domain_separator", e),
+    };
+
+    let sample = sample_attestation();
+    let attestation = PaymentAttestation {
+        intentHash: B256::from(sample.intent_hash),
+        amount: U256::from(sample.amount),
+        timestamp: U256::from(sample.timestamp),
+        paymentId: sample.payment_id.clone(),
+        dataHash: sample.data_hash(),
+    };
+    let digest = match chain.verifier_digest(attestation).await {
+        Ok(Some(digest)) => compare("digest", attestation_digest(domain, &sample), digest),
+        Ok(None) => unavailable("digest", "no verifier contract".to_string()),
+        Err(e) => unavailable("digest", e),
+    };
+
+    let threshold = match chain.verifier_min_witnesses().await {
+        Ok(Some(required)) if required <= U256::from(min_witnesses) => passed("min_witnesses"),
+        Ok(Some(required)) => failed(
+            "min_witnesses",
+            format!(
+                "verifier requires {} witness signatures, the service collects {}",
+                required, min_witnesses
+            ),
+        ),
+        Ok(None) => unavailable("min_witnesses", "no verifier contract".to_string()),
+        Err(e) => unavailable("min_witnesses", e),
+    };
+
+    checks.push(domain_separator);
+    checks.push(digest);
+    checks.push(threshold);
+
+    Some(SelfCheckReport {
+        verifier: format!("{}", verifier),
+        checked_at: current_timestamp(),
+        checks,
+        authorized,
+    })
+}
+
+fn compare(name: &'static str, local: B256, on_chain: B256) -> Check {
+    if local == on_chain {
+        passed(name)
+    } else {
+        failed(name, format!("local {} != on-chain {}", local, on_chain))
+    }
+}
+
+fn passed(name: &'static str) -> Check {
+    Check {
+        name,
+        witness: None,
+        status: CheckStatus::Passed,
+        detail: None,
+    }
+}
+
+fn failed(name: &'static str, detail: String) -> Check {
+    Check {
+        name,
+        witness: None,
+        status: CheckStatus::Failed,
+        detail: Some(detail),
+    }
+}
+
+fn unavailable(name: &'static str, detail: String) -> Check {
+    Check {
+        name,
+        witness: None,
+        status: CheckStatus::Unavailable,
+        detail: Some(detail),
+    }
+}
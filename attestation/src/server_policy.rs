@@ -0,0 +1,176 @@
+//! Allowed TLS server names
+//!
+//! Each entry of `ALLOWED_SERVERS` is either an exact DNS name
+//! (`thirdparty.qonto.com`) or a wildcard (`*.qonto.com`) matching exactly
+//! one extra label, as in TLS certificates. An entry may bind the payment
+//! provider whose parser handles it: `*.qonto.com=qonto`.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Server-name pattern of a policy entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerPattern {
+    /// Matches this name only
+    Exact(String),
+    /// `*.<domain>`: matches one label followed by `domain`
+    Wildcard(String),
+}
+
+/// One entry of the allowed server policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerPolicyEntry {
+    pub pattern: ServerPattern,
+    /// Name of the payment provider bound to this entry, if any
+    pub provider: Option<String>,
+}
+
+impl ServerPolicyEntry {
+    /// Whether `server_name` matches this entry
+    pub fn matches(&self, server_name: &str) -> bool {
+        let server_name = normalize(server_name);
+
+        match &self.pattern {
+            ServerPattern::Exact(name) => server_name == *name,
+            ServerPattern::Wildcard(domain) => server_name
+                .strip_suffix(domain.as_str())
+                .and_then(|prefix| prefix.strip_suffix('.'))
+                .is_some_and(is_label),
+        }
+    }
+}
+
+impl FromStr for ServerPolicyEntry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (pattern, provider) = match s.split_once('=') {
+            Some((pattern, provider)) => {
+                let provider = provider.trim();
+                if provider.is_empty() {
+                    return Err(format!("empty provider binding in {}", s));
+                }
+                (pattern.trim(), Some(provider.to_ascii_lowercase()))
+            }
+            None => (s.trim(), None),
+        };
+
+        let pattern = normalize(pattern);
+
+        let pattern = match pattern.strip_prefix("*.") {
+            // A wildcard must leave at least a registrable domain, e.g. not `*.com`
+            Some(domain) if is_dns_name(domain) && domain.contains('.') => {
+                ServerPattern::Wildcard(domain.to_string())
+            }
+            None if is_dns_name(&pattern) => ServerPattern::Exact(pattern),
+            _ => return Err(format!("invalid server name pattern {}", s.trim())),
+        };
+
+        Ok(Self { pattern, provider })
+    }
+}
+
+impl fmt::Display for ServerPolicyEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.pattern {
+            ServerPattern::Exact(name) => write!(f, "{}", name)?,
+            ServerPattern::Wildcard(domain) => write!(f, "*.{}", domain)?,
+        }
+        if let Some(provider) = &self.provider {
+            write!(f, "={}", provider)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a comma-separated server policy
+pub fn parse_server_policy(value: &str) -> Result<Vec<ServerPolicyEntry>, String> {
+    let entries = value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if entries.is_empty() {
+        return Err("server policy is empty".to_string());
+    }
+
+    Ok(entries)
+}
+
+/// Lowercase a DNS name and drop a trailing root dot
+fn normalize(name: &str) -> String {
+    name.trim().trim_end_matches('.').to_ascii_lowercase()
+}
+
+fn is_dns_name(name: &str) -> bool {
+    !name.is_empty() && name.split('.').all(is_label)
+}
+
+fn is_label(label: &str) -> bool {
+    !label.is_empty()
+        && label.len() <= 63
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+        && label.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(s: &str) -> ServerPolicyEntry {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_server_policy() {
+        let policy = parse_server_policy("thirdparty.qonto.com, *.Qonto.com=Qonto").unwrap();
+        assert_eq!(
+            policy,
+            vec![
+                ServerPolicyEntry {
+                    pattern: ServerPattern::Exact("thirdparty.qonto.com".to_string()),
+                    provider: None,
+                },
+                ServerPolicyEntry {
+                    pattern: ServerPattern::Wildcard("qonto.com".to_string()),
+                    provider: Some("qonto".to_string()),
+                },
+            ]
+        );
+        assert_eq!(policy[1].to_string(), "*.qonto.com=qonto");
+
+        for invalid in ["", "*.com", "*", "qonto.*", "thirdparty*.qonto.com", "a..b", "host=", "bad_host.com"] {
+            assert!(parse_server_policy(invalid).is_err(), "{} should be rejected", invalid);
+        }
+    }
+
+    #[test]
+    fn test_exact_match() {
+        let exact = entry("thirdparty.qonto.com");
+        assert!(exact.matches("thirdparty.qonto.com"));
+        assert!(exact.matches("ThirdParty.Qonto.com."));
+
+        for name in [
+            "thirdparty.qonto.com.attacker.net",
+            "evilthirdparty.qonto.com",
+            "api.thirdparty.qonto.com",
+            "qonto.com",
+        ] {
+            assert!(!exact.matches(name), "{} should not match", name);
+        }
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        let wildcard = entry("*.qonto.com");
+        assert!(wildcard.matches("thirdparty.qonto.com"));
+        assert!(wildcard.matches("api.qonto.com"));
+
+        for name in ["qonto.com", "a.b.qonto.com", "evilqonto.com", "api.qonto.com.attacker.net"] {
+            assert!(!wildcard.matches(name), "{} should not match", name);
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! In-process witness key, from the environment or an encrypted keystore
+
+use std::path::Path;
+
+use alloy_primitives::{keccak256, Address};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use k256::ecdsa::SigningKey;
+
+use super::{address_of, WitnessSigner};
+use crate::error::AttestationError;
+
+/// Signs with a secp256k1 key held in memory
+pub struct LocalSigner {
+    key: SigningKey,
+    address: Address,
+    backend: &'static str,
+}
+
+impl LocalSigner {
+    pub fn new(key: SigningKey) -> Self {
+        let address = address_of(key.verifying_key());
+        Self {
+            key,
+            address,
+            backend: "env",
+        }
+    }
+
+    /// Key from a hex string (`WITNESS_PRIVATE_KEY`)
+    pub fn from_hex(key_hex: &str) -> Result<Self> {
+        let key_bytes = hex::decode(key_hex.trim().trim_start_matches("0x"))
+            .map_err(|e| anyhow!("Invalid WITNESS_PRIVATE_KEY hex: {}", e))?;
+
+        let key = SigningKey::from_slice(&key_bytes)
+            .map_err(|e| anyhow!("Invalid WITNESS_PRIVATE_KEY: {}", e))?;
+
+        Ok(Self::new(key))
+    }
+
+    /// Key from an encrypted Web3 Secret Storage (V3) JSON keystore, as
+    /// written by geth, `cast wallet` or Foundry
+    pub fn from_keystore(path: impl AsRef<Path>, password: &str) -> Result<Self> {
+        let path = path.as_ref();
+        let key_bytes = eth_keystore::decrypt_key(path, password)
+            .map_err(|e| anyhow!("Cannot decrypt keystore {}: {}", path.display(), e))?;
+
+        let key = SigningKey::from_slice(&key_bytes)
+            .map_err(|e| anyhow!("Invalid key in keystore {}: {}", path.display(), e))?;
+
+        Ok(Self {
+            backend: "keystore",
+            ..Self::new(key)
+        })
+    }
+}
+
+#[async_trait]
+impl WitnessSigner for LocalSigner {
+    fn backend(&self) -> &'static str {
+        self.backend
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign(&self, message: &[u8]) -> Result<[u8; 65], AttestationError> {
+        let digest = keccak256(message);
+
+        let (signature, recovery_id) = self
+            .key
+            .sign_prehash_recoverable(&digest[..])
+            .map_err(|e| AttestationError::SigningError(format!("Failed to sign: {}", e)))?;
+
+        // Encode as 65-byte signature: r (32) || s (32) || v (1)
+        let mut sig_bytes = [0u8; 65];
+        sig_bytes[..64].copy_from_slice(&signature.to_bytes());
+        sig_bytes[64] = recovery_id.to_byte() + 27; // Ethereum v value
+
+        Ok(sig_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_keystore_signer() {
+        let dir = std::env::temp_dir().join(format!("freeflo-keystore-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let key = SigningKey::random(&mut rand::thread_rng());
+        eth_keystore::encrypt_key(
+            &dir,
+            &mut rand::thread_rng(),
+            key.to_bytes(),
+            "correct horse",
+            Some("witness.json"),
+        )
+        .unwrap();
+        let path = dir.join("witness.json");
+
+        let signer = LocalSigner::from_keystore(&path, "correct horse").unwrap();
+        assert_eq!(signer.backend(), "keystore");
+        assert_eq!(signer.address(), LocalSigner::new(key.clone()).address());
+
+        let env_signer = LocalSigner::new(key);
+        assert_eq!(
+            signer.sign(b"message").await.unwrap(),
+            env_signer.sign(b"message").await.unwrap()
+        );
+
+        assert!(LocalSigner::from_keystore(&path, "wrong password").is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_from_hex() {
+        // Anvil's first account
+        let signer = LocalSigner::from_hex(
+            "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .unwrap();
+        assert_eq!(
+            signer.address(),
+            "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266"
+                .parse::<Address>()
+                .unwrap()
+        );
+
+        assert!(LocalSigner::from_hex("0x1234").is_err());
+    }
+}
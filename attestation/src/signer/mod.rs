@@ -0,0 +1,178 @@
+//! Witness signer backends
+//!
+//! Attestations are signed through a `WitnessSigner`, so the witness key
+//! doesn't have to live in the process environment. `WITNESS_SIGNER` selects
+//! the backend:
+//!
+//! - `env` (default): raw key in `WITNESS_PRIVATE_KEY`
+//! - `keystore`: encrypted Web3 Secret Storage JSON file
+//! - `pkcs11`: key on a PKCS#11 token (built with the `pkcs11` feature)
+//! - `remote`: web3signer-style HTTP signer
+//!
+//! With several witness keys each key's variables carry its own prefix
+//! (`WITNESS_<ID>_SIGNER`, `WITNESS_<ID>_PRIVATE_KEY`, ...) instead of
+//! `WITNESS`. Secrets (keystore password, token PIN) are read from a file
+//! given in the `_FILE` variant of their variable, or else from the variable
+//! itself.
+
+mod local;
+#[cfg(feature = "pkcs11")]
+mod pkcs11;
+mod remote;
+
+use alloy_primitives::{keccak256, Address, B256};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+
+pub use local::LocalSigner;
+#[cfg(feature = "pkcs11")]
+pub use pkcs11::Pkcs11Signer;
+pub use remote::RemoteSigner;
+
+use crate::error::AttestationError;
+
+/// Signs attestation digests with the witness key
+#[async_trait]
+pub trait WitnessSigner: Send + Sync {
+    /// Short backend name, e.g. "keystore"
+    fn backend(&self) -> &'static str;
+
+    /// Address the verifier must list in `authorizedWitnesses`
+    fn address(&self) -> Address;
+
+    /// Sign `keccak256(message)`, returning the Ethereum `r || s || v`
+    /// signature with `v` in {27, 28}
+    async fn sign(&self, message: &[u8]) -> Result<[u8; 65], AttestationError>;
+}
+
+/// Create the signer selected by `<prefix>_SIGNER`, e.g. `WITNESS_SIGNER`
+pub fn from_env(prefix: &str) -> Result<Box<dyn WitnessSigner>> {
+    let backend = std::env::var(format!("{}_SIGNER", prefix)).unwrap_or_else(|_| "env".to_string());
+
+    match backend.trim().to_lowercase().as_str() {
+        "env" => {
+            let key_hex = var(prefix, "PRIVATE_KEY")?;
+            Ok(Box::new(LocalSigner::from_hex(&key_hex)?))
+        }
+        "keystore" => {
+            let path = var(prefix, "KEYSTORE_PATH")?;
+            let password = secret(&format!("{}_KEYSTORE_PASSWORD", prefix))?;
+            Ok(Box::new(LocalSigner::from_keystore(&path, &password)?))
+        }
+        #[cfg(feature = "pkcs11")]
+        "pkcs11" => Ok(Box::new(Pkcs11Signer::from_env(prefix)?)),
+        #[cfg(not(feature = "pkcs11"))]
+        "pkcs11" => Err(anyhow!(
+            "{}_SIGNER=pkcs11 requires building with the pkcs11 feature",
+            prefix
+        )),
+        "remote" => Ok(Box::new(RemoteSigner::from_env(prefix)?)),
+        other => Err(anyhow!(
+            "Invalid {}_SIGNER: {} (expected env, keystore, pkcs11 or remote)",
+            prefix,
+            other
+        )),
+    }
+}
+
+/// Read the required variable `<prefix>_<name>`
+fn var(prefix: &str, name: &str) -> Result<String> {
+    let name = format!("{}_{}", prefix, name);
+    std::env::var(&name).map_err(|_| anyhow!("{} not set", name))
+}
+
+/// Read a secret from the file named by `<name>_FILE`, or else from `<name>`
+fn secret(name: &str) -> Result<String> {
+    if let Ok(path) = std::env::var(format!("{}_FILE", name)) {
+        let value = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("Cannot read {}_FILE {}: {}", name, path, e))?;
+        return Ok(value.trim_end_matches(['\r', '\n']).to_string());
+    }
+
+    std::env::var(name).map_err(|_| anyhow!("{} or {}_FILE not set", name, name))
+}
+
+/// Ethereum address of a secp256k1 public key
+pub fn address_of(key: &VerifyingKey) -> Address {
+    let point = key.to_encoded_point(false);
+    Address::from_slice(&keccak256(&point.as_bytes()[1..])[12..])
+}
+
+/// Encode a signature of `digest` as `r || s || v`, normalizing `s` to the
+/// lower half (as OpenZeppelin's ECDSA requires) and picking the recovery id
+/// that yields `address`.
+///
+/// Backends that return a bare `(r, s)` or an untrusted signature go through
+/// this, so a wrong key is caught before an attestation is returned.
+pub fn recoverable_signature(
+    signature: &Signature,
+    digest: &B256,
+    address: Address,
+) -> Result<[u8; 65], AttestationError> {
+    let signature = signature.normalize_s().unwrap_or(*signature);
+
+    let recovery_id = (0..2)
+        .filter_map(RecoveryId::from_byte)
+        .find(|&id| {
+            VerifyingKey::recover_from_prehash(&digest[..], &signature, id)
+                .is_ok_and(|key| address_of(&key) == address)
+        })
+        .ok_or_else(|| {
+            AttestationError::SigningError(format!("signature does not recover to {}", address))
+        })?;
+
+    let mut sig_bytes = [0u8; 65];
+    sig_bytes[..64].copy_from_slice(&signature.to_bytes());
+    sig_bytes[64] = recovery_id.to_byte() + 27;
+    Ok(sig_bytes)
+}
+
+/// Address that produced an `r || s || v` signature of `digest`
+pub fn recover_address(digest: &B256, signature: &[u8]) -> Result<Address, AttestationError> {
+    let invalid = |e: String| AttestationError::SigningError(format!("invalid signature: {}", e));
+
+    if signature.len() != 65 {
+        return Err(invalid(format!("{} bytes, expected 65", signature.len())));
+    }
+    let parsed = Signature::from_slice(&signature[..64]).map_err(|e| invalid(e.to_string()))?;
+    let recovery_id = signature[64]
+        .checked_sub(27)
+        .and_then(RecoveryId::from_byte)
+        .ok_or_else(|| invalid(format!("v = {}", signature[64])))?;
+
+    VerifyingKey::recover_from_prehash(&digest[..], &parsed, recovery_id)
+        .map(|key| address_of(&key))
+        .map_err(|e| invalid(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::SigningKey;
+
+    #[test]
+    fn test_recoverable_signature() {
+        let key = SigningKey::random(&mut rand::thread_rng());
+        let address = address_of(key.verifying_key());
+        let digest = keccak256(b"attestation");
+
+        let (signature, _) = key.sign_prehash_recoverable(&digest[..]).unwrap();
+
+        // A high-s signature is normalized before encoding
+        let high_s = Signature::from_scalars(signature.r(), -*signature.s()).unwrap();
+        for signature in [signature, high_s] {
+            let encoded = recoverable_signature(&signature, &digest, address).unwrap();
+            let s = Signature::from_slice(&encoded[..64]).unwrap();
+            assert!(s.normalize_s().is_none());
+
+            assert_eq!(recover_address(&digest, &encoded).unwrap(), address);
+        }
+
+        let other = SigningKey::random(&mut rand::thread_rng());
+        assert!(
+            recoverable_signature(&signature, &digest, address_of(other.verifying_key())).is_err()
+        );
+        assert!(recover_address(&digest, &[0u8; 64]).is_err());
+    }
+}
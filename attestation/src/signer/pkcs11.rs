@@ -0,0 +1,153 @@
+//! Witness key on a PKCS#11 token (HSM, or SoftHSM for testing)
+//!
+//! The key is an EC key pair on the secp256k1 curve, found by label. The
+//! token signs the digest with `CKM_ECDSA` and returns a bare `(r, s)`; the
+//! recovery id is found against the public key read from the token.
+
+use std::sync::Mutex;
+
+use alloy_primitives::{keccak256, Address};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use cryptoki::context::{CInitializeArgs, Pkcs11};
+use cryptoki::mechanism::Mechanism;
+use cryptoki::object::{Attribute, AttributeType, ObjectClass, ObjectHandle};
+use cryptoki::session::{Session, UserType};
+use cryptoki::types::AuthPin;
+use k256::ecdsa::{Signature, VerifyingKey};
+
+use super::{address_of, recoverable_signature, secret, var, WitnessSigner};
+use crate::error::AttestationError;
+
+/// Signs with a private key held on a PKCS#11 token
+pub struct Pkcs11Signer {
+    // Keep the library loaded for as long as the session lives
+    _context: Pkcs11,
+    session: Mutex<Session>,
+    key: ObjectHandle,
+    address: Address,
+}
+
+impl Pkcs11Signer {
+    /// Open a session on the token labelled `token_label` of the PKCS#11
+    /// `module` and find the key pair labelled `key_label`
+    pub fn new(module: &str, token_label: &str, key_label: &str, pin: &str) -> Result<Self> {
+        let context = Pkcs11::new(module)
+            .map_err(|e| anyhow!("Cannot load PKCS#11 module {}: {}", module, e))?;
+        context
+            .initialize(CInitializeArgs::OsThreads)
+            .map_err(|e| anyhow!("Cannot initialize PKCS#11 module: {}", e))?;
+
+        let slot = context
+            .get_slots_with_token()
+            .map_err(|e| anyhow!("Cannot list PKCS#11 slots: {}", e))?
+            .into_iter()
+            .find(|slot| {
+                context
+                    .get_token_info(*slot)
+                    .is_ok_and(|info| info.label().trim() == token_label)
+            })
+            .ok_or_else(|| anyhow!("No PKCS#11 token labelled {}", token_label))?;
+
+        let session = context
+            .open_ro_session(slot)
+            .map_err(|e| anyhow!("Cannot open PKCS#11 session: {}", e))?;
+        session
+            .login(UserType::User, Some(&AuthPin::new(pin.to_string())))
+            .map_err(|e| anyhow!("PKCS#11 login failed: {}", e))?;
+
+        let key = find_object(&session, ObjectClass::PRIVATE_KEY, key_label)?;
+        let public_key = find_object(&session, ObjectClass::PUBLIC_KEY, key_label)?;
+        let address = address_of(&read_public_key(&session, public_key)?);
+
+        Ok(Self {
+            _context: context,
+            session: Mutex::new(session),
+            key,
+            address,
+        })
+    }
+
+    /// Create from `<prefix>_PKCS11_MODULE`, `<prefix>_PKCS11_TOKEN`,
+    /// `<prefix>_PKCS11_KEY_LABEL` and `<prefix>_PKCS11_PIN`
+    pub fn from_env(prefix: &str) -> Result<Self> {
+        Self::new(
+            &var(prefix, "PKCS11_MODULE")?,
+            &var(prefix, "PKCS11_TOKEN")?,
+            &var(prefix, "PKCS11_KEY_LABEL")?,
+            &secret(&format!("{}_PKCS11_PIN", prefix))?,
+        )
+    }
+}
+
+/// The single object of `class` labelled `label`
+fn find_object(session: &Session, class: ObjectClass, label: &str) -> Result<ObjectHandle> {
+    let objects = session
+        .find_objects(&[
+            Attribute::Class(class),
+            Attribute::Label(label.as_bytes().to_vec()),
+        ])
+        .map_err(|e| anyhow!("Cannot search PKCS#11 objects: {}", e))?;
+
+    match objects.as_slice() {
+        [object] => Ok(*object),
+        [] => Err(anyhow!("No PKCS#11 {} labelled {}", class, label)),
+        _ => Err(anyhow!(
+            "Several PKCS#11 objects {} labelled {}",
+            class,
+            label
+        )),
+    }
+}
+
+/// Public key from `CKA_EC_POINT`, which holds a DER OCTET STRING wrapping
+/// the uncompressed point (some modules return the bare point)
+fn read_public_key(session: &Session, public_key: ObjectHandle) -> Result<VerifyingKey> {
+    let attributes = session
+        .get_attributes(public_key, &[AttributeType::EcPoint])
+        .map_err(|e| anyhow!("Cannot read PKCS#11 public key: {}", e))?;
+
+    let point = attributes
+        .into_iter()
+        .find_map(|attribute| match attribute {
+            Attribute::EcPoint(point) => Some(point),
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("PKCS#11 public key has no EC point"))?;
+
+    let point = match point.as_slice() {
+        [0x04, len, rest @ ..] if *len as usize == rest.len() => rest,
+        bare => bare,
+    };
+
+    VerifyingKey::from_sec1_bytes(point)
+        .map_err(|e| anyhow!("PKCS#11 key is not a secp256k1 key: {}", e))
+}
+
+#[async_trait]
+impl WitnessSigner for Pkcs11Signer {
+    fn backend(&self) -> &'static str {
+        "pkcs11"
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign(&self, message: &[u8]) -> Result<[u8; 65], AttestationError> {
+        let digest = keccak256(message);
+
+        let raw = self
+            .session
+            .lock()
+            .map_err(|_| AttestationError::SigningError("PKCS#11 session poisoned".to_string()))?
+            .sign(&Mechanism::Ecdsa, self.key, &digest[..])
+            .map_err(|e| AttestationError::SigningError(format!("PKCS#11 sign failed: {}", e)))?;
+
+        let signature = Signature::from_slice(&raw).map_err(|e| {
+            AttestationError::SigningError(format!("Invalid PKCS#11 signature: {}", e))
+        })?;
+
+        recoverable_signature(&signature, &digest, self.address)
+    }
+}
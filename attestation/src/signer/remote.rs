@@ -0,0 +1,109 @@
+//! Remote HTTP signer with the web3signer `eth1` API
+//!
+//! `POST {url}/api/v1/eth1/sign/{public key}` with `{"data": "0x..."}` signs
+//! `keccak256(data)` and answers with the hex `r || s || v` signature. The
+//! service sends the EIP-712 message, so the signer hashes it to the digest.
+
+use std::time::Duration;
+
+use alloy_primitives::{keccak256, Address};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use k256::ecdsa::{Signature, VerifyingKey};
+
+use super::{address_of, recoverable_signature, var, WitnessSigner};
+use crate::error::AttestationError;
+
+/// Time allowed for the remote signer to answer
+const SIGN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Signs through a web3signer-compatible HTTP service
+pub struct RemoteSigner {
+    url: String,
+    /// Key identifier in the signer's API: the hex public key
+    public_key: String,
+    address: Address,
+    http_client: reqwest::Client,
+}
+
+impl RemoteSigner {
+    /// `public_key` is the hex secp256k1 public key the signer holds, with or
+    /// without the `04` prefix of an uncompressed key
+    pub fn new(url: &str, public_key: &str) -> Result<Self> {
+        let public_key = public_key.trim();
+        let mut key_bytes = hex::decode(public_key.trim_start_matches("0x"))
+            .map_err(|e| anyhow!("Invalid remote signer public key hex: {}", e))?;
+        if key_bytes.len() == 64 {
+            key_bytes.insert(0, 0x04);
+        }
+
+        let key = VerifyingKey::from_sec1_bytes(&key_bytes)
+            .map_err(|e| anyhow!("Invalid remote signer public key: {}", e))?;
+
+        let http_client = reqwest::Client::builder()
+            .timeout(SIGN_TIMEOUT)
+            .build()
+            .map_err(|e| anyhow!("Cannot create HTTP client: {}", e))?;
+
+        Ok(Self {
+            url: url.trim_end_matches('/').to_string(),
+            public_key: public_key.to_string(),
+            address: address_of(&key),
+            http_client,
+        })
+    }
+
+    /// Create from `<prefix>_REMOTE_SIGNER_URL` and `<prefix>_REMOTE_SIGNER_KEY`
+    pub fn from_env(prefix: &str) -> Result<Self> {
+        Self::new(
+            &var(prefix, "REMOTE_SIGNER_URL")?,
+            &var(prefix, "REMOTE_SIGNER_KEY")?,
+        )
+    }
+}
+
+#[async_trait]
+impl WitnessSigner for RemoteSigner {
+    fn backend(&self) -> &'static str {
+        "remote"
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign(&self, message: &[u8]) -> Result<[u8; 65], AttestationError> {
+        let failed = |e: String| AttestationError::SigningError(format!("Remote signer: {}", e));
+
+        let response = self
+            .http_client
+            .post(format!("{}/api/v1/eth1/sign/{}", self.url, self.public_key))
+            .json(&serde_json::json!({ "data": format!("0x{}", hex::encode(message)) }))
+            .send()
+            .await
+            .map_err(|e| failed(format!("request failed: {}", e)))?;
+
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| failed(format!("cannot read response: {}", e)))?;
+        if !status.is_success() {
+            return Err(failed(format!("{}: {}", status, body.trim())));
+        }
+
+        let sig_bytes = hex::decode(body.trim().trim_matches('"').trim_start_matches("0x"))
+            .map_err(|e| failed(format!("invalid signature hex: {}", e)))?;
+        if sig_bytes.len() != 65 {
+            return Err(failed(format!(
+                "signature is {} bytes, expected 65",
+                sig_bytes.len()
+            )));
+        }
+        let signature = Signature::from_slice(&sig_bytes[..64])
+            .map_err(|e| failed(format!("invalid signature: {}", e)))?;
+
+        // Don't trust the remote `v`: recover it against our address instead
+        recoverable_signature(&signature, &keccak256(message), self.address)
+    }
+}
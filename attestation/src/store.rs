@@ -0,0 +1,350 @@
+//! Persistent attestation state
+//!
+//! Replay protection: each proven payment may fill one intent only. The
+//! on-chain verifier enforces this through its nullifiers, but a solver would
+//! only find out when its transaction reverts. The store therefore records,
+//! per server and transaction ID, the intent a payment was attested for, and
+//! the service refuses to attest the same payment for any other intent.
+//!
+//! Signed attestations are kept by intent hash, so a solver that lost a
+//! response can fetch it again and an identical resubmission returns the
+//! original signature without verifying the presentation again.
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::attestation::AttestationResponse;
+use crate::error::AttestationError;
+use crate::verification::VerifiedPayment;
+
+/// An attestation signed for an intent
+#[derive(Debug, Clone)]
+pub struct StoredAttestation {
+    /// Solver the attestation was signed for
+    pub solver_address: String,
+    /// Keccak-256 of the submitted (base64) presentation
+    pub presentation_hash: [u8; 32],
+    pub response: AttestationResponse,
+}
+
+/// Persistent record of attested payments and signed attestations
+pub struct AttestationStore {
+    conn: Mutex<Connection>,
+}
+
+impl AttestationStore {
+    /// Open (or create) the store in a SQLite database file
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    /// An in-memory store, for tests
+    pub fn in_memory() -> anyhow::Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> anyhow::Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS attested_payments (
+                server TEXT NOT NULL,
+                transaction_id TEXT NOT NULL,
+                intent_hash TEXT NOT NULL,
+                solver_address TEXT NOT NULL,
+                attested_at INTEGER NOT NULL,
+                PRIMARY KEY (server, transaction_id)
+            );
+            CREATE TABLE IF NOT EXISTS attestations (
+                intent_hash TEXT PRIMARY KEY,
+                solver_address TEXT NOT NULL,
+                presentation_hash TEXT NOT NULL,
+                response TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            );",
+        )?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Bind a verified payment to an intent.
+    ///
+    /// Succeeds if the payment is new or already bound to the same intent,
+    /// so a solver can retry an attestation. Fails if the payment has no
+    /// transaction ID or was attested for a different intent.
+    pub fn claim_payment(
+        &self,
+        verified: &VerifiedPayment,
+        intent_hash: &[u8; 32],
+        solver_address: &str,
+        now: u64,
+    ) -> Result<(), AttestationError> {
+        let transaction_id = verified
+            .transaction_id
+            .as_deref()
+            .ok_or_else(|| AttestationError::MissingField("transaction_id".to_string()))?;
+        let server = verified.server_name.to_ascii_lowercase();
+        let intent_hash = hex_key(intent_hash);
+        let conn = self.lock()?;
+
+        conn.execute(
+            "INSERT OR IGNORE INTO attested_payments
+                (server, transaction_id, intent_hash, solver_address, attested_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                server,
+                transaction_id,
+                intent_hash,
+                solver_address,
+                now as i64
+            ],
+        )
+        .map_err(store_error)?;
+
+        let bound: String = conn
+            .query_row(
+                "SELECT intent_hash FROM attested_payments WHERE server = ?1 AND transaction_id = ?2",
+                params![server, transaction_id],
+                |row| row.get(0),
+            )
+            .map_err(store_error)?;
+
+        if bound != intent_hash {
+            return Err(AttestationError::PaymentAlreadyAttested {
+                transaction_id: transaction_id.to_string(),
+                intent_hash: bound,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Keep a signed attestation for its intent. An attestation already
+    /// stored for the intent is kept.
+    pub fn save_attestation(
+        &self,
+        intent_hash: &[u8; 32],
+        solver_address: &str,
+        presentation_hash: &[u8; 32],
+        response: &AttestationResponse,
+        now: u64,
+    ) -> Result<(), AttestationError> {
+        let response = serde_json::to_string(response)
+            .map_err(|e| AttestationError::Internal(format!("store: {}", e)))?;
+
+        self.lock()?
+            .execute(
+                "INSERT OR IGNORE INTO attestations
+                    (intent_hash, solver_address, presentation_hash, response, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    hex_key(intent_hash),
+                    solver_address,
+                    hex_key(presentation_hash),
+                    response,
+                    now as i64
+                ],
+            )
+            .map_err(store_error)?;
+
+        Ok(())
+    }
+
+    /// The attestation signed for an intent, if any
+    pub fn attestation(
+        &self,
+        intent_hash: &[u8; 32],
+    ) -> Result<Option<StoredAttestation>, AttestationError> {
+        let row: Option<(String, String, String)> = self
+            .lock()?
+            .query_row(
+                "SELECT solver_address, presentation_hash, response
+                 FROM attestations WHERE intent_hash = ?1",
+                params![hex_key(intent_hash)],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()
+            .map_err(store_error)?;
+
+        let Some((solver_address, presentation_hash, response)) = row else {
+            return Ok(None);
+        };
+
+        let corrupt =
+            |e: String| AttestationError::Internal(format!("store: corrupt attestation: {}", e));
+        let mut hash = [0u8; 32];
+        hex::decode_to_slice(presentation_hash.trim_start_matches("0x"), &mut hash)
+            .map_err(|e| corrupt(e.to_string()))?;
+
+        Ok(Some(StoredAttestation {
+            solver_address,
+            presentation_hash: hash,
+            response: serde_json::from_str(&response).map_err(|e| corrupt(e.to_string()))?,
+        }))
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>, AttestationError> {
+        self.conn
+            .lock()
+            .map_err(|_| AttestationError::Internal("store lock poisoned".to_string()))
+    }
+}
+
+fn hex_key(bytes: &[u8; 32]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn store_error(e: rusqlite::Error) -> AttestationError {
+    AttestationError::Internal(format!("store: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payment(server: &str, transaction_id: &str) -> VerifiedPayment {
+        VerifiedPayment {
+            server_name: server.to_string(),
+            transaction_id: Some(transaction_id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_payment_binds_to_first_intent() {
+        let store = AttestationStore::in_memory().unwrap();
+        let tx = payment("thirdparty.qonto.com", "tx-1");
+
+        assert!(store
+            .claim_payment(&tx, &[1u8; 32], "0xsolver", 100)
+            .is_ok());
+        // Retrying for the same intent is allowed
+        assert!(store
+            .claim_payment(&tx, &[1u8; 32], "0xsolver", 200)
+            .is_ok());
+
+        match store.claim_payment(&tx, &[2u8; 32], "0xother", 300) {
+            Err(AttestationError::PaymentAlreadyAttested { intent_hash, .. }) => {
+                assert_eq!(intent_hash, format!("0x{}", hex::encode([1u8; 32])))
+            }
+            other => panic!("expected replay rejection, got {:?}", other),
+        }
+
+        // Server names are compared case-insensitively
+        let upper = payment("ThirdParty.Qonto.com", "tx-1");
+        assert!(store
+            .claim_payment(&upper, &[2u8; 32], "0xother", 300)
+            .is_err());
+
+        // The same ID on another server is a different payment
+        assert!(store
+            .claim_payment(
+                &payment("other.example.com", "tx-1"),
+                &[2u8; 32],
+                "0xsolver",
+                300
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn test_rejects_payment_without_id() {
+        let store = AttestationStore::in_memory().unwrap();
+        let mut tx = payment("thirdparty.qonto.com", "tx-1");
+        tx.transaction_id = None;
+
+        assert!(matches!(
+            store.claim_payment(&tx, &[1u8; 32], "0xsolver", 100),
+            Err(AttestationError::MissingField(_))
+        ));
+    }
+
+    fn response(signature: &str) -> AttestationResponse {
+        AttestationResponse {
+            success: true,
+            signature: signature.to_string(),
+            signatures: Vec::new(),
+            digest: "0xdigest".to_string(),
+            data_hash: "0xdata".to_string(),
+            schema_version: crate::eip712::SchemaVersion::V1,
+            v2: None,
+            payment: crate::attestation::PaymentDetails {
+                transaction_id: Some("tx-1".to_string()),
+                amount_cents: 10000,
+                currency: "EUR".to_string(),
+                beneficiary_iban: "DE89370400440532013000".to_string(),
+                status: "completed".to_string(),
+                side: "debit".to_string(),
+                timestamp: 1700000000,
+                server: "thirdparty.qonto.com".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_keeps_first_attestation_per_intent() {
+        let store = AttestationStore::in_memory().unwrap();
+        assert!(store.attestation(&[1u8; 32]).unwrap().is_none());
+
+        store
+            .save_attestation(
+                &[1u8; 32],
+                "0xsolver",
+                &[7u8; 32],
+                &response("0xfirst"),
+                100,
+            )
+            .unwrap();
+        store
+            .save_attestation(
+                &[1u8; 32],
+                "0xother",
+                &[8u8; 32],
+                &response("0xsecond"),
+                200,
+            )
+            .unwrap();
+
+        let stored = store.attestation(&[1u8; 32]).unwrap().unwrap();
+        assert_eq!(stored.solver_address, "0xsolver");
+        assert_eq!(stored.presentation_hash, [7u8; 32]);
+        assert_eq!(stored.response.signature, "0xfirst");
+        assert_eq!(stored.response.payment.amount_cents, 10000);
+
+        assert!(store.attestation(&[2u8; 32]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_persists_across_reopen() {
+        let path =
+            std::env::temp_dir().join(format!("attestation-store-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        AttestationStore::open(&path)
+            .unwrap()
+            .claim_payment(
+                &payment("thirdparty.qonto.com", "tx-1"),
+                &[1u8; 32],
+                "0xsolver",
+                100,
+            )
+            .unwrap();
+
+        let reopened = AttestationStore::open(&path).unwrap();
+        assert!(reopened
+            .claim_payment(
+                &payment("thirdparty.qonto.com", "tx-1"),
+                &[2u8; 32],
+                "0xsolver",
+                200
+            )
+            .is_err());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
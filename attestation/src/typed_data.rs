@@ -0,0 +1,194 @@
+//! Generic EIP-712 typed data
+//!
+//! Hashes any `sol!` struct under an EIP-712 domain. The type string, field
+//! encoding and struct hash come from `alloy_sol_types::SolStruct`, so they
+//! follow the struct definition instead of being encoded by hand.
+//!
+//! The test vectors below are shared with `contracts/test/PaymentVerifier.t.sol`
+//! (`test_MatchesServiceVectors`): both sides must reproduce them, so the
+//! service and `PaymentVerifier._hashAttestation` cannot drift apart.
+
+use std::borrow::Cow;
+
+use alloy_primitives::{keccak256, Address, B256, U256};
+use alloy_sol_types::{Eip712Domain, SolStruct};
+
+/// EIP-712 domain with a name, version, chain ID and verifying contract
+pub fn domain(
+    name: &str,
+    version: &str,
+    chain_id: u64,
+    verifying_contract: Address,
+) -> Eip712Domain {
+    Eip712Domain::new(
+        Some(Cow::Owned(name.to_string())),
+        Some(Cow::Owned(version.to_string())),
+        Some(U256::from(chain_id)),
+        Some(verifying_contract),
+        None,
+    )
+}
+
+/// A struct to sign under an EIP-712 domain
+#[derive(Debug, Clone)]
+pub struct TypedData<T: SolStruct> {
+    pub domain: Eip712Domain,
+    pub message: T,
+}
+
+impl<T: SolStruct> TypedData<T> {
+    pub fn new(domain: Eip712Domain, message: T) -> Self {
+        Self { domain, message }
+    }
+
+    /// `encodeType` of the struct, e.g. `PaymentAttestation(bytes32 intentHash,...)`
+    pub fn encode_type() -> Cow<'static, str> {
+        T::eip712_encode_type()
+    }
+
+    /// keccak256 of the struct's type string
+    pub fn type_hash() -> B256 {
+        keccak256(Self::encode_type().as_bytes())
+    }
+
+    pub fn domain_separator(&self) -> B256 {
+        self.domain.separator()
+    }
+
+    /// `hashStruct` of the message
+    pub fn struct_hash(&self) -> B256 {
+        self.message.eip712_hash_struct()
+    }
+
+    /// `\x19\x01 || domainSeparator || hashStruct(message)`, whose keccak256
+    /// is the digest
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoded = Vec::with_capacity(66);
+        encoded.extend_from_slice(&[0x19, 0x01]);
+        encoded.extend_from_slice(&self.domain_separator()[..]);
+        encoded.extend_from_slice(&self.struct_hash()[..]);
+        encoded
+    }
+
+    /// The digest a signer signs
+    pub fn digest(&self) -> B256 {
+        keccak256(self.encode())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eip712::{PaymentAttestation, PaymentAttestationV2};
+    use alloy_primitives::{address, b256};
+
+    /// `PaymentVerifier` as deployed by the Solidity vector test: the second
+    /// contract created by forge's default test contract address
+    const VERIFIER: Address = address!("2e234DAe75C793f67A35089C9d99245E1C58470b");
+    const CHAIN_ID: u64 = 84532;
+
+    /// The attestation of the Solidity tests: intent 1, €100.00, `tx-123-abc`
+    fn attestation() -> PaymentAttestation {
+        PaymentAttestation {
+            intentHash: B256::with_last_byte(1),
+            amount: U256::from(10000),
+            timestamp: U256::from(1703500000),
+            paymentId: "tx-123-abc".to_string(),
+            dataHash: keccak256("test data"),
+        }
+    }
+
+    #[test]
+    fn test_payment_attestation_vectors() {
+        let typed = TypedData::new(
+            domain("WisePaymentVerifier", "1", CHAIN_ID, VERIFIER),
+            attestation(),
+        );
+
+        // PaymentVerifier.PAYMENT_ATTESTATION_TYPEHASH
+        assert_eq!(
+            TypedData::<PaymentAttestation>::encode_type(),
+            "PaymentAttestation(bytes32 intentHash,uint256 amount,uint256 timestamp,string paymentId,bytes32 dataHash)"
+        );
+        assert_eq!(
+            TypedData::<PaymentAttestation>::type_hash(),
+            b256!("e95e77676d6a81cd4b39cfc7f84fc889a78db30963ad8e89cc6ae7c19c9fa960")
+        );
+        // PaymentVerifier.DOMAIN_SEPARATOR
+        assert_eq!(
+            typed.domain_separator(),
+            b256!("5446b5d6fbdba6805931a1e60d955a2644bb6156941854df2d37ef48c304d6f5")
+        );
+        // PaymentVerifier._hashAttestation
+        assert_eq!(
+            typed.struct_hash(),
+            b256!("c9edd42c964033f73537a36a05e8e2f112f9628a3caac8163bb81700fea256fa")
+        );
+        // PaymentVerifier.getDigest
+        assert_eq!(
+            typed.digest(),
+            b256!("ca05fe80a4187afb0bac15470a51ab239d752b43f9393bddeddffa2b959184b0")
+        );
+        assert_eq!(typed.encode().len(), 66);
+    }
+
+    #[test]
+    fn test_domain_fields_change_the_separator() {
+        let separator = domain("WisePaymentVerifier", "1", CHAIN_ID, VERIFIER).separator();
+
+        assert_ne!(
+            domain("WisePaymentVerifier", "2", CHAIN_ID, VERIFIER).separator(),
+            separator
+        );
+        assert_ne!(
+            domain("OtherVerifier", "1", CHAIN_ID, VERIFIER).separator(),
+            separator
+        );
+        assert_ne!(
+            domain("WisePaymentVerifier", "1", 8453, VERIFIER).separator(),
+            separator
+        );
+        assert_ne!(
+            domain("WisePaymentVerifier", "1", CHAIN_ID, Address::ZERO).separator(),
+            separator
+        );
+    }
+
+    #[test]
+    fn test_payment_attestation_v2_vectors() {
+        let base = attestation();
+        let typed = TypedData::new(
+            domain("WisePaymentVerifier", "2", CHAIN_ID, VERIFIER),
+            PaymentAttestationV2 {
+                intentHash: base.intentHash,
+                amount: base.amount,
+                timestamp: base.timestamp,
+                paymentId: base.paymentId,
+                dataHash: base.dataHash,
+                currency: 0,
+                rtpn: 0,
+                solver: Address::repeat_byte(0x22),
+                beneficiaryHash: keccak256("DE89370400440532013000"),
+                executedAt: U256::from(1703499900),
+                validUntil: U256::from(1703501800),
+            },
+        );
+
+        assert_eq!(
+            TypedData::<PaymentAttestationV2>::type_hash(),
+            b256!("ab4988d31e612fa623a700be6b043ae89af09656fbd1796fbaa9c0660e176c57")
+        );
+        assert_eq!(
+            typed.domain_separator(),
+            b256!("5f7dc25fd35e28274ecaa8d9fabf177131819fc98926889aade232911865a298")
+        );
+        assert_eq!(
+            typed.struct_hash(),
+            b256!("802a81e02537e3629a02c027a8d5c5aa60134d5536bb60edfd37e6f0d755f054")
+        );
+        assert_eq!(
+            typed.digest(),
+            b256!("1e111120892140d987bb0ff506e9f3f85561f48d38bd770223e6db32638887cd")
+        );
+    }
+}
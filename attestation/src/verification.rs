@@ -1,12 +1,18 @@
+use std::ops::Range;
+
 use tlsn::attestation::{
     presentation::{Presentation, PresentationOutput},
+    signing::{KeyAlgId, VerifyingKey},
     CryptoProvider,
 };
 
 use crate::error::AttestationError;
+use crate::http::verify_http_exchange;
+use crate::json::parse_disclosed;
+use crate::providers::{ProviderRegistry, StatusPolicy};
 
 /// Verified payment information extracted from a TLSNotary presentation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct VerifiedPayment {
     /// Server name (e.g., "thirdparty.qonto.com")
     pub server_name: String,
@@ -23,22 +29,93 @@ pub struct VerifiedPayment {
     /// Amount in the smallest currency unit (cents for EUR)
     pub amount_cents: Option<i64>,
     
+    /// ISO 4217 currency code of the amount
+    pub currency: Option<String>,
+    
     /// Beneficiary IBAN
     pub beneficiary_iban: Option<String>,
     
+    /// Beneficiary (counterparty) name
+    pub beneficiary_name: Option<String>,
+    
     /// Transaction status
     pub status: Option<String>,
+    
+    /// Transaction side ("debit" for outgoing, "credit" for incoming)
+    pub side: Option<String>,
+    
+    /// Transfer reference, which must carry the intent's payment reference
+    pub reference: Option<String>,
+    
+    /// When the bank executed the transfer (unix seconds)
+    pub executed_at: Option<u64>,
+}
+
+/// Check that a verified payment is settled and outgoing according to its
+/// provider's status policy
+pub fn check_status_policy(
+    verified: &VerifiedPayment,
+    policy: &StatusPolicy,
+) -> Result<(), AttestationError> {
+    let status = verified.status.as_deref()
+        .ok_or_else(|| AttestationError::MissingField("status".to_string()))?;
+    if !status.eq_ignore_ascii_case(policy.status) {
+        return Err(AttestationError::InvalidPaymentStatus(format!(
+            "expected status {}, got {}",
+            policy.status, status
+        )));
+    }
+    
+    let side = verified.side.as_deref()
+        .ok_or_else(|| AttestationError::MissingField("side".to_string()))?;
+    if !side.eq_ignore_ascii_case(policy.side) {
+        return Err(AttestationError::InvalidPaymentStatus(format!(
+            "expected side {}, got {}",
+            policy.side, side
+        )));
+    }
+    
+    Ok(())
+}
+
+/// Checks a decoded presentation and reads the payment it proves
+pub trait PresentationVerifier: Send + Sync {
+    fn verify(
+        &self,
+        presentation: &[u8],
+        providers: &ProviderRegistry,
+        trusted_notary_keys: &[Vec<u8>],
+    ) -> Result<VerifiedPayment, AttestationError>;
+}
+
+/// Verifies TLSNotary presentations with `verify_presentation`
+pub struct TlsnVerifier;
+
+impl PresentationVerifier for TlsnVerifier {
+    fn verify(
+        &self,
+        presentation: &[u8],
+        providers: &ProviderRegistry,
+        trusted_notary_keys: &[Vec<u8>],
+    ) -> Result<VerifiedPayment, AttestationError> {
+        verify_presentation(presentation, providers, trusted_notary_keys)
+    }
 }
 
 /// Verify a TLSNotary presentation and extract payment information
 pub fn verify_presentation(
     presentation_bytes: &[u8],
-    allowed_servers: &[String],
+    providers: &ProviderRegistry,
+    trusted_notary_keys: &[Vec<u8>],
 ) -> Result<VerifiedPayment, AttestationError> {
     // Deserialize the presentation
     let presentation: Presentation = bincode::deserialize(presentation_bytes)
         .map_err(|e| AttestationError::DeserializationError(format!("Failed to deserialize presentation: {}", e)))?;
     
+    // Only accept attestations signed by a pinned notary. Signature validity
+    // alone proves nothing: anyone can run a notary with their own key.
+    check_notary_key(presentation.verifying_key(), trusted_notary_keys)?;
+    
     // Use default crypto provider (trusts standard root CAs)
     let crypto_provider = CryptoProvider::default();
     
@@ -56,13 +133,9 @@ pub fn verify_presentation(
         .ok_or(AttestationError::ServerNotFound)?
         .to_string();
     
-    // Check if server is in allowed list
-    if !allowed_servers.iter().any(|s| server_name.contains(s)) {
-        return Err(AttestationError::UnexpectedServer {
-            expected: allowed_servers.join(", "),
-            actual: server_name,
-        });
-    }
+    // Only servers matching the policy are accepted; the entry they match
+    // decides which provider parses the response
+    let provider = providers.resolve(&server_name)?;
     
     // Extract transcript
     let mut partial_transcript = transcript
@@ -71,225 +144,174 @@ pub fn verify_presentation(
     // Mark unauthenticated bytes
     partial_transcript.set_unauthed(b'X');
     
-    // Extract the response body from the received data
-    let received = String::from_utf8_lossy(partial_transcript.received_unsafe());
+    let sent = partial_transcript.sent_unsafe();
+    let received = partial_transcript.received_unsafe();
+    let sent_authed = authed_mask(sent.len(), partial_transcript.sent_authed().iter_ranges());
+    let authed = authed_mask(received.len(), partial_transcript.received_authed().iter_ranges());
+    
+    // Parse the transcript as HTTP and check the endpoint, host, status and content type
+    let exchange = verify_http_exchange(
+        sent,
+        &sent_authed,
+        received,
+        &server_name,
+        provider.endpoints(),
+    )?;
     
-    // Parse HTTP response to extract JSON body
-    let response_body = extract_json_body(&received)?;
+    // Only authenticated bytes of the body are read
     
-    // Extract payment details from JSON
-    let (transaction_id, amount_cents, beneficiary_iban, status) = parse_payment_details(&response_body)?;
+    let body = exchange.body;
+    let value = parse_disclosed(&received[body.clone()], &authed[body.clone()])?;
+    let response_body = String::from_utf8_lossy(&received[body]).into_owned();
+    
+    // Extract payment details from the disclosed JSON
+    let fields = provider.parse_payment(&value)?;
     
     Ok(VerifiedPayment {
         server_name,
         timestamp: connection_info.time,
         response_body,
-        transaction_id,
-        amount_cents,
-        beneficiary_iban,
-        status,
+        transaction_id: fields.transaction_id,
+        amount_cents: fields.amount_cents,
+        currency: fields.currency,
+        beneficiary_iban: fields.beneficiary_iban,
+        beneficiary_name: fields.beneficiary_name,
+        status: fields.status,
+        side: fields.side,
+        reference: fields.reference,
+        executed_at: fields.executed_at,
     })
 }
 
-/// Extract JSON body from HTTP response (with selective disclosure handling)
-fn extract_json_body(response: &str) -> Result<String, AttestationError> {
-    // Find the start of body (after headers)
-    // Look for double CRLF or double LF that separates headers from body
-    let body_start = response
-        .find("\r\n\r\n")
-        .map(|i| i + 4)
-        .or_else(|| response.find("\n\n").map(|i| i + 2))
-        .ok_or_else(|| AttestationError::InvalidPaymentData("Could not find response body".to_string()))?;
-    
-    let body = &response[body_start..];
-    
-    // For selectively disclosed responses, the body contains revealed values
-    // interspersed with 'X' for redacted content. We need to extract visible fields.
-    //
-    // Example with selective disclosure:
-    // XXXXXXX019b2249-50b2-7778-8b9eXXXXXXEI - MALYEN MalekXXXXX
-    //
-    // We extract the visible (non-X) runs of text
-    
-    // First try to find a proper JSON structure
-    if let Some(json_start) = body.find('{') {
-        let json_body = &body[json_start..];
-        if let Some(json_end) = json_body.rfind('}') {
-            return Ok(json_body[..=json_end].to_string());
-        }
+/// Whether each of the first `len` transcript bytes lies in an authenticated range
+fn authed_mask(len: usize, authed: impl Iterator<Item = Range<usize>>) -> Vec<bool> {
+    let mut mask = vec![false; len];
+    for range in authed {
+        mask[range.start.min(len)..range.end.min(len)].fill(true);
     }
-    
-    // If no JSON structure, extract visible content for manual parsing
-    // This is for selectively disclosed content
-    let visible_content = extract_visible_content(body);
-    
-    if visible_content.is_empty() {
-        return Err(AttestationError::InvalidPaymentData("No visible content in response body".to_string()));
-    }
-    
-    // Try to reconstruct a minimal JSON from visible content
-    // For now, return the raw visible content for debugging
-    Ok(format!("{{\"_visible_content\": {:?}}}", visible_content))
+    mask
 }
 
-/// Extract visible (non-redacted) content from a selectively disclosed transcript
-fn extract_visible_content(body: &str) -> Vec<String> {
-    let mut visible_parts = Vec::new();
-    let mut current_part = String::new();
-    
-    for c in body.chars() {
-        if c == 'X' {
-            if !current_part.is_empty() {
-                visible_parts.push(current_part.clone());
-                current_part.clear();
-            }
-        } else {
-            current_part.push(c);
-        }
+/// Tolerated clock skew between the notary and this service, in seconds
+const MAX_CLOCK_SKEW_SECS: u64 = 60;
+
+/// Check that a TLS session is neither in the future nor older than `max_age_secs`
+pub fn check_session_freshness(
+    session_time: u64,
+    now: u64,
+    max_age_secs: u64,
+) -> Result<(), AttestationError> {
+    if session_time > now + MAX_CLOCK_SKEW_SECS {
+        return Err(AttestationError::InvalidSessionTime(format!(
+            "session time {} is in the future (now {})",
+            session_time, now
+        )));
     }
     
-    if !current_part.is_empty() {
-        visible_parts.push(current_part);
+    let age = now.saturating_sub(session_time);
+    if age > max_age_secs {
+        return Err(AttestationError::InvalidSessionTime(format!(
+            "session is {}s old, maximum is {}s",
+            age, max_age_secs
+        )));
     }
     
-    // Filter out very short noise strings (less than 3 chars)
-    visible_parts.into_iter()
-        .filter(|s| s.len() >= 3 && s.chars().any(|c| c.is_alphanumeric()))
-        .collect()
+    Ok(())
 }
 
-/// Parse payment details from Qonto transaction JSON or selectively disclosed content
-fn parse_payment_details(json: &str) -> Result<(Option<String>, Option<i64>, Option<String>, Option<String>), AttestationError> {
-    // First try standard JSON parsing
-    if let Ok(value) = serde_json::from_str::<serde_json::Value>(json) {
-        // Check for _visible_content (selective disclosure fallback)
-        if let Some(visible) = value.get("_visible_content") {
-            // This is extracted visible content, not proper JSON
-            // For now, return None values - in production, we'd parse this more intelligently
-            return Ok((None, None, None, None));
-        }
-        
-        // Try to extract from Qonto transaction format
-        // Format: { "transaction": { ... } } or { "transactions": [...] }
-        let tx = value.get("transaction")
-            .or_else(|| value.get("transactions").and_then(|t| t.get(0)))
-            .or_else(|| value.get("transfer")); // Also try transfer format
-        
-        let transaction_id = tx
-            .and_then(|t| t.get("id"))
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string());
-        
-        // Amount is in the transaction, could be "amount" or "local_amount"
-        let amount_cents: Option<i64> = tx
-            .and_then(|t| t.get("amount_cents"))
-            .or_else(|| tx.and_then(|t| t.get("local_amount_cents")))
-            .and_then(|v| v.as_i64())
-            .or_else(|| {
-                // Also try to get amount in decimal format and convert
-                tx.and_then(|t| t.get("amount"))
-                    .and_then(|v| v.as_f64())
-                    .map(|a| (a * 100.0) as i64)
-            })
-            .and_then(|v| if v == 0 { None } else { Some(v) });
-        
-        // For SEPA transfers, beneficiary IBAN can be in different locations:
-        // - Qonto transactions: transfer.counterparty_account_number
-        // - Other formats: counterparty.iban, beneficiary.iban, beneficiary_iban
-        let beneficiary_iban = tx
-            .and_then(|t| t.get("transfer"))
-            .and_then(|t| t.get("counterparty_account_number"))
-            .or_else(|| tx.and_then(|t| t.get("counterparty")).and_then(|c| c.get("iban")))
-            .or_else(|| tx.and_then(|t| t.get("counterparty")).and_then(|c| c.get("account_number")))
-            .or_else(|| tx.and_then(|t| t.get("beneficiary")).and_then(|b| b.get("iban")))
-            .or_else(|| tx.and_then(|t| t.get("beneficiary_iban")))
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string());
-        
-        let status = tx
-            .and_then(|t| t.get("status"))
-            .or_else(|| tx.and_then(|t| t.get("operation_type")))
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string());
-        
-        return Ok((transaction_id, amount_cents, beneficiary_iban, status));
+/// Check that the notary key which signed the attestation is trusted
+fn check_notary_key(
+    key: &VerifyingKey,
+    trusted_notary_keys: &[Vec<u8>],
+) -> Result<(), AttestationError> {
+    if key.alg != KeyAlgId::K256 {
+        return Err(AttestationError::UntrustedNotary(format!(
+            "unsupported key algorithm {}",
+            key.alg
+        )));
     }
     
-    // If JSON parsing fails, try to extract from raw content (selective disclosure)
-    // Look for UUID patterns (transfer IDs), amounts, and IBAN patterns
-    let transaction_id = extract_uuid(json);
-    let beneficiary_iban = extract_iban(json);
-    let amount_cents = extract_amount(json);
-    
-    Ok((transaction_id, amount_cents, beneficiary_iban, None))
-}
-
-/// Extract UUID pattern from string (for transaction IDs)
-fn extract_uuid(s: &str) -> Option<String> {
-    let uuid_regex = regex::Regex::new(
-        r"[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}"
-    ).ok()?;
+    if !trusted_notary_keys.iter().any(|k| k == &key.data) {
+        return Err(AttestationError::UntrustedNotary(format!("0x{}", hex::encode(&key.data))));
+    }
     
-    uuid_regex.find(s).map(|m| m.as_str().to_string())
-}
-
-/// Extract IBAN pattern from string
-fn extract_iban(s: &str) -> Option<String> {
-    // IBAN format: 2 letters, 2 digits, then alphanumeric (12-30 chars total)
-    let iban_regex = regex::Regex::new(
-        r"[A-Z]{2}[0-9]{2}[A-Z0-9]{10,28}"
-    ).ok()?;
-    
-    iban_regex.find(s).map(|m| m.as_str().to_string())
-}
-
-/// Extract amount from string (looking for decimal or integer amounts)
-fn extract_amount(s: &str) -> Option<i64> {
-    // Look for amount patterns like "100.00" or "10000"
-    let amount_regex = regex::Regex::new(r"(\d+)\.?(\d{0,2})").ok()?;
-    
-    // This is very basic - in production we'd want more context
-    amount_regex.find(s).and_then(|m| {
-        let amount_str = m.as_str();
-        if amount_str.contains('.') {
-            // Parse as decimal, convert to cents
-            amount_str.parse::<f64>().ok().map(|a| (a * 100.0) as i64)
-        } else {
-            // Already in cents
-            amount_str.parse::<i64>().ok()
-        }
-    })
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::providers::{PaymentProvider, Qonto};
+    
+    fn qonto_payment(status: &str, side: &str) -> VerifiedPayment {
+        VerifiedPayment {
+            server_name: "thirdparty.qonto.com".to_string(),
+            status: Some(status.to_string()),
+            side: Some(side.to_string()),
+            ..Default::default()
+        }
+    }
+    
+    #[test]
+    fn test_status_policy_accepts_completed_debit() {
+        let policy = Qonto.status_policy();
+        assert!(check_status_policy(&qonto_payment("completed", "debit"), &policy).is_ok());
+    }
     
     #[test]
-    fn test_extract_json_body() {
-        let response = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"test\": \"value\"}";
-        let body = extract_json_body(response).unwrap();
-        assert_eq!(body, "{\"test\": \"value\"}");
+    fn test_status_policy_rejects_unsettled_or_incoming() {
+        let policy = Qonto.status_policy();
+        for (status, side) in [("pending", "debit"), ("declined", "debit"), ("completed", "credit")] {
+            assert!(matches!(
+                check_status_policy(&qonto_payment(status, side), &policy),
+                Err(AttestationError::InvalidPaymentStatus(_))
+            ));
+        }
+        
+        let mut payment = qonto_payment("completed", "debit");
+        payment.side = None;
+        assert!(matches!(
+            check_status_policy(&payment, &policy),
+            Err(AttestationError::MissingField(_))
+        ));
     }
     
     #[test]
-    fn test_parse_qonto_transaction() {
-        let json = r#"{
-            "transaction": {
-                "id": "tx-123",
-                "amount_cents": 10000,
-                "status": "completed",
-                "counterparty": {
-                    "iban": "DE89370400440532013000"
-                }
-            }
-        }"#;
+    fn test_check_session_freshness() {
+        let now = 1_700_000_000;
+        
+        assert!(check_session_freshness(now, now, 3600).is_ok());
+        assert!(check_session_freshness(now - 3600, now, 3600).is_ok());
+        assert!(check_session_freshness(now + MAX_CLOCK_SKEW_SECS, now, 3600).is_ok());
+        
+        assert!(matches!(
+            check_session_freshness(now - 3601, now, 3600),
+            Err(AttestationError::InvalidSessionTime(_))
+        ));
+        assert!(matches!(
+            check_session_freshness(now + MAX_CLOCK_SKEW_SECS + 1, now, 3600),
+            Err(AttestationError::InvalidSessionTime(_))
+        ));
+    }
+    
+    #[test]
+    fn test_check_notary_key() {
+        let trusted = vec![vec![2u8; 33]];
+        
+        let key = VerifyingKey { alg: KeyAlgId::K256, data: vec![2u8; 33] };
+        assert!(check_notary_key(&key, &trusted).is_ok());
+        
+        let key = VerifyingKey { alg: KeyAlgId::K256, data: vec![3u8; 33] };
+        assert!(matches!(
+            check_notary_key(&key, &trusted),
+            Err(AttestationError::UntrustedNotary(_))
+        ));
         
-        let (id, amount, iban, status) = parse_payment_details(json).unwrap();
-        assert_eq!(id, Some("tx-123".to_string()));
-        assert_eq!(amount, Some(10000));
-        assert_eq!(iban, Some("DE89370400440532013000".to_string()));
-        assert_eq!(status, Some("completed".to_string()));
+        let key = VerifyingKey { alg: KeyAlgId::P256, data: vec![2u8; 33] };
+        assert!(matches!(
+            check_notary_key(&key, &trusted),
+            Err(AttestationError::UntrustedNotary(_))
+        ));
     }
 }
 
@@ -0,0 +1,350 @@
+//! Witness keys and their rotation
+//!
+//! `PaymentVerifier` can trust several witnesses at once, so a key can be
+//! rotated without downtime: add the new witness on-chain, configure its key
+//! with an activation time, and give the old key a retirement time.
+//!
+//! `WITNESS_KEYS` lists the key IDs (e.g. `2026q1,2026q2`); each key is
+//! configured by its `WITNESS_<ID>_*` signer variables plus
+//! `WITNESS_<ID>_ACTIVE_FROM` and `WITNESS_<ID>_RETIRE_AT` (unix seconds or
+//! RFC 3339). Without `WITNESS_KEYS` the single key of the `WITNESS_*`
+//! variables is used, always active.
+//!
+//! The primary key, which signs, is the most recently activated key that is
+//! active and confirmed authorized by the verifier self-check. A new key
+//! therefore only takes over once the chain trusts it, and no key signs
+//! before the first confirmation. Without a verifier contract nothing can be
+//! confirmed and activation alone decides.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use alloy_primitives::Address;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use crate::error::AttestationError;
+use crate::signer::{self, WitnessSigner};
+
+/// Lifecycle status of a witness key at a given time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyStatus {
+    /// Before its activation time
+    Pending,
+    /// Between activation and retirement
+    Active,
+    /// At or after its retirement time
+    Retired,
+}
+
+/// A witness key with its validity window
+pub struct WitnessKey {
+    pub id: String,
+    pub signer: Box<dyn WitnessSigner>,
+    /// Unix time from which the key may sign (always, if `None`)
+    pub active_from: Option<u64>,
+    /// Unix time from which the key no longer signs (never, if `None`)
+    pub retire_at: Option<u64>,
+}
+
+impl WitnessKey {
+    pub fn status(&self, now: u64) -> KeyStatus {
+        if self.retire_at.is_some_and(|t| now >= t) {
+            KeyStatus::Retired
+        } else if self.active_from.is_some_and(|t| now < t) {
+            KeyStatus::Pending
+        } else {
+            KeyStatus::Active
+        }
+    }
+}
+
+/// A key as shown on the health endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct WitnessKeyInfo {
+    pub id: String,
+    pub address: String,
+    pub signer: &'static str,
+    pub status: KeyStatus,
+    pub active_from: Option<u64>,
+    pub retire_at: Option<u64>,
+    /// Whether the verifier trusts the key (`None` if unknown)
+    pub authorized: Option<bool>,
+    pub primary: bool,
+}
+
+/// The configured witness keys
+pub struct WitnessKeys {
+    keys: Vec<WitnessKey>,
+    /// Whether a key must be confirmed authorized before it signs
+    require_authorization: AtomicBool,
+    /// Latest known verifier authorization per address
+    authorized: Mutex<HashMap<Address, bool>>,
+}
+
+impl WitnessKeys {
+    pub fn new(keys: Vec<WitnessKey>) -> Result<Self> {
+        if keys.is_empty() {
+            return Err(anyhow!("At least one witness key must be configured"));
+        }
+
+        for (i, key) in keys.iter().enumerate() {
+            if let (Some(from), Some(until)) = (key.active_from, key.retire_at) {
+                if from >= until {
+                    return Err(anyhow!("Witness key {} retires before it activates", key.id));
+                }
+            }
+            if keys[..i].iter().any(|k| k.signer.address() == key.signer.address()) {
+                return Err(anyhow!(
+                    "Witness key {} duplicates address {}",
+                    key.id,
+                    key.signer.address()
+                ));
+            }
+        }
+
+        Ok(Self {
+            keys,
+            require_authorization: AtomicBool::new(false),
+            authorized: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Load the keys listed in `WITNESS_KEYS`, or the single `WITNESS_*` key
+    pub fn from_env() -> Result<Self> {
+        let Ok(ids) = std::env::var("WITNESS_KEYS") else {
+            return Self::new(vec![WitnessKey {
+                id: "default".to_string(),
+                signer: signer::from_env("WITNESS")?,
+                active_from: None,
+                retire_at: None,
+            }]);
+        };
+
+        let keys = ids
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(|id| {
+                let prefix = format!("WITNESS_{}", env_name(id));
+                Ok(WitnessKey {
+                    id: id.to_string(),
+                    signer: signer::from_env(&prefix)?,
+                    active_from: time_var(&format!("{}_ACTIVE_FROM", prefix))?,
+                    retire_at: time_var(&format!("{}_RETIRE_AT", prefix))?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Self::new(keys)
+    }
+
+    pub fn keys(&self) -> &[WitnessKey] {
+        &self.keys
+    }
+
+    /// Addresses the self-check should confirm: every key not yet retired
+    pub fn unretired_addresses(&self, now: u64) -> Vec<Address> {
+        self.keys
+            .iter()
+            .filter(|k| k.status(now) != KeyStatus::Retired)
+            .map(|k| k.signer.address())
+            .collect()
+    }
+
+    /// Only sign with keys the verifier self-check confirmed, once a
+    /// verifier contract is configured
+    pub fn require_authorization(&self) {
+        self.require_authorization.store(true, Ordering::Relaxed);
+    }
+
+    /// Record the verifier's answers from a self-check. A key whose check
+    /// failed keeps its previous state.
+    pub fn record_authorization(&self, results: &[(Address, Option<bool>)]) {
+        let mut authorized = self.authorized.lock().unwrap();
+        for (address, result) in results {
+            if let Some(result) = result {
+                authorized.insert(*address, *result);
+            }
+        }
+    }
+
+    fn is_authorized(&self, address: Address) -> Option<bool> {
+        self.authorized.lock().unwrap().get(&address).copied()
+    }
+
+    /// The key that signs at `now`, if any
+    pub fn primary(&self, now: u64) -> Option<&WitnessKey> {
+        let required = self.require_authorization.load(Ordering::Relaxed);
+
+        self.keys
+            .iter()
+            .filter(|k| k.status(now) == KeyStatus::Active)
+            .filter(|k| !required || self.is_authorized(k.signer.address()) == Some(true))
+            .max_by_key(|k| k.active_from.unwrap_or(0))
+    }
+
+    /// Signer of the primary key at `now`
+    pub fn signer(&self, now: u64) -> Result<&dyn WitnessSigner, AttestationError> {
+        self.primary(now)
+            .map(|k| k.signer.as_ref())
+            .ok_or_else(|| {
+                AttestationError::SigningError(
+                    "no active witness key is authorized by the verifier".to_string(),
+                )
+            })
+    }
+
+    /// Every key with its status at `now`
+    pub fn describe(&self, now: u64) -> Vec<WitnessKeyInfo> {
+        let primary = self.primary(now).map(|k| k.signer.address());
+
+        self.keys
+            .iter()
+            .map(|k| {
+                let address = k.signer.address();
+                WitnessKeyInfo {
+                    id: k.id.clone(),
+                    address: format!("{}", address),
+                    signer: k.signer.backend(),
+                    status: k.status(now),
+                    active_from: k.active_from,
+                    retire_at: k.retire_at,
+                    authorized: self.is_authorized(address),
+                    primary: primary == Some(address),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Environment variable form of a key ID: `2026-q1` -> `2026_Q1`
+fn env_name(id: &str) -> String {
+    id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
+
+/// Optional time variable, in unix seconds or RFC 3339
+fn time_var(name: &str) -> Result<Option<u64>> {
+    let Ok(value) = std::env::var(name) else {
+        return Ok(None);
+    };
+    let value = value.trim();
+
+    if let Ok(secs) = value.parse::<u64>() {
+        return Ok(Some(secs));
+    }
+
+    chrono::DateTime::parse_from_rfc3339(value)
+        .ok()
+        .and_then(|t| u64::try_from(t.timestamp()).ok())
+        .map(Some)
+        .ok_or_else(|| anyhow!("Invalid {}: {} (expected unix seconds or RFC 3339)", name, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::LocalSigner;
+    use k256::ecdsa::SigningKey;
+
+    fn key(id: &str, active_from: Option<u64>, retire_at: Option<u64>) -> WitnessKey {
+        WitnessKey {
+            id: id.to_string(),
+            signer: Box::new(LocalSigner::new(SigningKey::random(&mut rand::thread_rng()))),
+            active_from,
+            retire_at,
+        }
+    }
+
+    fn primary_id(keys: &WitnessKeys, now: u64) -> Option<&str> {
+        keys.primary(now).map(|k| k.id.as_str())
+    }
+
+    #[test]
+    fn test_key_status() {
+        let key = key("a", Some(100), Some(200));
+        assert_eq!(key.status(99), KeyStatus::Pending);
+        assert_eq!(key.status(100), KeyStatus::Active);
+        assert_eq!(key.status(199), KeyStatus::Active);
+        assert_eq!(key.status(200), KeyStatus::Retired);
+    }
+
+    #[test]
+    fn test_rotation_waits_for_authorization() {
+        let keys = WitnessKeys::new(vec![
+            key("old", None, Some(300)),
+            key("new", Some(200), None),
+        ])
+        .unwrap();
+        let old = keys.keys()[0].signer.address();
+        let new = keys.keys()[1].signer.address();
+
+        // Unchecked: activation alone decides
+        assert_eq!(primary_id(&keys, 100), Some("old"));
+        assert_eq!(primary_id(&keys, 200), Some("new"));
+
+        // Nothing is confirmed yet
+        keys.require_authorization();
+        assert_eq!(primary_id(&keys, 100), None);
+
+        // The new key is active but not yet trusted on-chain
+        keys.record_authorization(&[(old, Some(true)), (new, Some(false))]);
+        assert_eq!(primary_id(&keys, 250), Some("old"));
+
+        // A failed check keeps the last known state
+        keys.record_authorization(&[(old, None), (new, None)]);
+        assert_eq!(primary_id(&keys, 250), Some("old"));
+
+        keys.record_authorization(&[(new, Some(true))]);
+        assert_eq!(primary_id(&keys, 250), Some("new"));
+
+        // The old key stops signing at retirement even if nothing replaces it
+        keys.record_authorization(&[(new, Some(false))]);
+        assert_eq!(primary_id(&keys, 299), Some("old"));
+        assert_eq!(primary_id(&keys, 300), None);
+        assert!(keys.signer(300).is_err());
+
+        let info = keys.describe(250);
+        assert_eq!(info[0].status, KeyStatus::Active);
+        assert!(info[0].primary);
+        assert_eq!(info[1].authorized, Some(false));
+        assert!(!info[1].primary);
+    }
+
+    #[test]
+    fn test_invalid_key_sets() {
+        assert!(WitnessKeys::new(vec![]).is_err());
+        assert!(WitnessKeys::new(vec![key("a", Some(200), Some(100))]).is_err());
+
+        let duplicate = WitnessKey {
+            id: "b".to_string(),
+            signer: Box::new(LocalSigner::new(SigningKey::from_slice(&[7u8; 32]).unwrap())),
+            active_from: None,
+            retire_at: None,
+        };
+        let same = WitnessKey {
+            id: "c".to_string(),
+            signer: Box::new(LocalSigner::new(SigningKey::from_slice(&[7u8; 32]).unwrap())),
+            active_from: None,
+            retire_at: None,
+        };
+        assert!(WitnessKeys::new(vec![key("a", None, None), duplicate, same]).is_err());
+    }
+
+    #[test]
+    fn test_env_names_and_times() {
+        assert_eq!(env_name("2026-q1"), "2026_Q1");
+
+        std::env::set_var("WITNESS_TEST_TIME_SECS", "1767225600");
+        std::env::set_var("WITNESS_TEST_TIME_RFC3339", "2026-01-01T00:00:00Z");
+        std::env::set_var("WITNESS_TEST_TIME_INVALID", "next tuesday");
+        assert_eq!(time_var("WITNESS_TEST_TIME_SECS").unwrap(), Some(1767225600));
+        assert_eq!(time_var("WITNESS_TEST_TIME_RFC3339").unwrap(), Some(1767225600));
+        assert!(time_var("WITNESS_TEST_TIME_INVALID").is_err());
+        assert_eq!(time_var("WITNESS_TEST_TIME_UNSET").unwrap(), None);
+    }
+}
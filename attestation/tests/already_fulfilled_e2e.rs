@@ -0,0 +1,157 @@
+//! E2E tests for the on-chain pre-flight checks
+//!
+//! An intent already fulfilled, or a payment ID already consumed as an
+//! OffRampV3 transfer ID or PaymentVerifier nullifier, can never settle: the
+//! service reports it as `AlreadyFulfilled` instead of signing.
+
+mod common;
+
+use alloy_primitives::Address;
+use alloy_sol_types::SolCall;
+use attestation_service::attestation::AttestationRequest;
+use attestation_service::chain::{
+    check_intent_unfulfilled, check_payment_unused, getIntentCall, isNullifierUsedCall,
+    usedTransferIdsCall,
+};
+use attestation_service::cluster::WitnessCluster;
+use attestation_service::eip712::SchemaVersion;
+use attestation_service::error::AttestationError;
+use common::{
+    app_state, chain_client, encode_bool, encode_intent_response, mock_rpc_calls, serve,
+    IntentFixture,
+};
+use k256::ecdsa::SigningKey;
+
+const VERIFIER_CONTRACT: &str = "0xd54e8219d30c2d04a8faec64657f06f440889d70";
+
+fn verifier() -> Address {
+    VERIFIER_CONTRACT.parse().unwrap()
+}
+
+#[tokio::test]
+async fn test_fulfilled_intent_is_rejected() {
+    let server = mock_rpc_calls(&[(
+        getIntentCall::SELECTOR,
+        encode_intent_response(&IntentFixture {
+            status: 3, // Fulfilled
+            transfer_id: [7u8; 32],
+            ..Default::default()
+        }),
+    )])
+    .await;
+
+    let chain = chain_client(server.uri()).with_verifier(verifier());
+    let err = check_intent_unfulfilled(&chain, [1u8; 32])
+        .await
+        .expect_err("A fulfilled intent must be rejected");
+
+    assert!(
+        matches!(err, AttestationError::AlreadyFulfilled(_)),
+        "Unexpected error: {:?}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_committed_intent_passes() {
+    let server = mock_rpc_calls(&[(
+        getIntentCall::SELECTOR,
+        encode_intent_response(&IntentFixture::default()),
+    )])
+    .await;
+
+    let chain = chain_client(server.uri()).with_verifier(verifier());
+    assert!(check_intent_unfulfilled(&chain, [1u8; 32]).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_unused_payment_passes() {
+    let server = mock_rpc_calls(&[
+        (usedTransferIdsCall::SELECTOR, encode_bool(false)),
+        (isNullifierUsedCall::SELECTOR, encode_bool(false)),
+    ])
+    .await;
+
+    let chain = chain_client(server.uri()).with_verifier(verifier());
+    assert!(check_payment_unused(&chain, "tx-1").await.is_ok());
+}
+
+#[tokio::test]
+async fn test_used_transfer_id_is_rejected() {
+    let server = mock_rpc_calls(&[
+        (usedTransferIdsCall::SELECTOR, encode_bool(true)),
+        (isNullifierUsedCall::SELECTOR, encode_bool(false)),
+    ])
+    .await;
+
+    let chain = chain_client(server.uri()).with_verifier(verifier());
+    let err = check_payment_unused(&chain, "tx-1")
+        .await
+        .expect_err("A used transfer ID must be rejected");
+
+    assert!(
+        matches!(err, AttestationError::AlreadyFulfilled(_)),
+        "Unexpected error: {:?}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_used_nullifier_is_rejected() {
+    let server = mock_rpc_calls(&[
+        (usedTransferIdsCall::SELECTOR, encode_bool(false)),
+        (isNullifierUsedCall::SELECTOR, encode_bool(true)),
+    ])
+    .await;
+
+    let chain = chain_client(server.uri()).with_verifier(verifier());
+    let err = check_payment_unused(&chain, "tx-1")
+        .await
+        .expect_err("A used nullifier must be rejected");
+
+    assert!(
+        matches!(err, AttestationError::AlreadyFulfilled(_)),
+        "Unexpected error: {:?}",
+        err
+    );
+
+    // Without a verifier contract only the transfer ID is checked
+    let offramp_only = chain_client(server.uri());
+    assert!(check_payment_unused(&offramp_only, "tx-1").await.is_ok());
+}
+
+#[tokio::test]
+async fn test_stated_payment_id_is_checked_before_verification() {
+    let server = mock_rpc_calls(&[
+        (
+            getIntentCall::SELECTOR,
+            encode_intent_response(&IntentFixture::default()),
+        ),
+        (usedTransferIdsCall::SELECTOR, encode_bool(true)),
+        (isNullifierUsedCall::SELECTOR, encode_bool(false)),
+    ])
+    .await;
+    let mut state = app_state(
+        SigningKey::random(&mut rand::thread_rng()),
+        WitnessCluster::new(Vec::new(), None, 1).unwrap(),
+    );
+    state.chain = Some(chain_client(server.uri()).with_verifier(verifier()));
+    let service = serve(state).await;
+
+    // The presentation is never decoded: the used payment ID is refused first
+    let response = reqwest::Client::new()
+        .post(format!("{}/api/v1/attest", service))
+        .json(&AttestationRequest {
+            presentation: "not a presentation".to_string(),
+            intent_hash: format!("0x{}", "11".repeat(32)),
+            expected_amount_cents: 10000,
+            expected_beneficiary_iban: "DE89370400440532013000".to_string(),
+            payment_id: Some("tx-1".to_string()),
+            schema_version: SchemaVersion::V1,
+        })
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 409);
+}
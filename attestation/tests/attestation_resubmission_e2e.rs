@@ -0,0 +1,92 @@
+//! E2E tests for resubmitting an attested intent
+//!
+//! A stored attestation is returned again only for the same solver,
+//! presentation and schema version. Anything else is a conflict, since the
+//! intent is already attested.
+
+mod common;
+
+use alloy_primitives::keccak256;
+use attestation_service::attestation::{AttestationRequest, AttestationResponse};
+use attestation_service::audit::current_timestamp;
+use attestation_service::cluster::WitnessCluster;
+use attestation_service::eip712::SchemaVersion;
+use common::{app_state, attestation_response, serve};
+use k256::ecdsa::SigningKey;
+
+/// Solver address used while solver authentication is disabled
+const UNAUTHENTICATED_SOLVER: &str = "0x0000000000000000000000000000000000000000";
+
+fn request(presentation: &str, schema_version: SchemaVersion) -> AttestationRequest {
+    AttestationRequest {
+        presentation: presentation.to_string(),
+        intent_hash: format!("0x{}", "11".repeat(32)),
+        expected_amount_cents: 10000,
+        expected_beneficiary_iban: "DE89370400440532013000".to_string(),
+        payment_id: None,
+        schema_version,
+    }
+}
+
+/// URL of a service that already signed the schema v1 attestation of
+/// `presentation` for intent `0x11..11`
+async fn service_with_stored_attestation(presentation: &str) -> String {
+    let key = SigningKey::random(&mut rand::thread_rng());
+    let state = app_state(key, WitnessCluster::new(Vec::new(), None, 1).unwrap());
+    state
+        .store
+        .save_attestation(
+            &[0x11; 32],
+            UNAUTHENTICATED_SOLVER,
+            &keccak256(presentation.as_bytes()).0,
+            &attestation_response(),
+            current_timestamp(),
+        )
+        .unwrap();
+
+    serve(state).await
+}
+
+async fn attest(service: &str, request: &AttestationRequest) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/api/v1/attest", service))
+        .json(request)
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_identical_resubmission_returns_stored_attestation() {
+    let service = service_with_stored_attestation("cHJlc2VudGF0aW9u").await;
+
+    let response = attest(&service, &request("cHJlc2VudGF0aW9u", SchemaVersion::V1)).await;
+
+    assert_eq!(response.status(), 200);
+    let attestation: AttestationResponse = response.json().await.unwrap();
+    assert_eq!(attestation.signature, attestation_response().signature);
+    assert_eq!(attestation.schema_version, SchemaVersion::V1);
+}
+
+#[tokio::test]
+async fn test_resubmission_under_another_schema_is_a_conflict() {
+    let service = service_with_stored_attestation("cHJlc2VudGF0aW9u").await;
+
+    // The stored v1 signature must not be handed out as a v2 attestation
+    let response = attest(&service, &request("cHJlc2VudGF0aW9u", SchemaVersion::V2)).await;
+
+    assert_eq!(response.status(), 409);
+}
+
+#[tokio::test]
+async fn test_other_presentation_for_attested_intent_is_a_conflict() {
+    let service = service_with_stored_attestation("cHJlc2VudGF0aW9u").await;
+
+    let response = attest(
+        &service,
+        &request("b3RoZXIgcHJlc2VudGF0aW9u", SchemaVersion::V1),
+    )
+    .await;
+
+    assert_eq!(response.status(), 409);
+}
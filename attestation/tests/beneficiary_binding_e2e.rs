@@ -0,0 +1,64 @@
+//! E2E tests for binding the proven beneficiary to the on-chain intent
+//!
+//! With chain validation enabled, the expected IBAN comes from the intent's
+//! `receivingInfo` and the proven counterparty name is fuzzy-matched against
+//! `recipientName`. The solver-supplied IBAN in the request is advisory only.
+
+mod common;
+
+use attestation_service::chain::validate_intent;
+use common::{mock_chain, verified_payment, IntentFixture, SOLVER};
+
+#[tokio::test]
+async fn test_matching_beneficiary_is_accepted() {
+    let (_server, chain) = mock_chain(IntentFixture::default()).await;
+
+    // Proof IBAN has spaces and the name is uppercased and reordered
+    let result = validate_intent(&chain, [1u8; 32], SOLVER, &verified_payment(Some(10000)), true).await;
+
+    assert!(result.is_ok(), "Expected acceptance, got {:?}", result);
+}
+
+#[tokio::test]
+async fn test_iban_different_from_receiving_info_is_rejected() {
+    let (_server, chain) = mock_chain(IntentFixture {
+        receiving_info: "FR7630006000011234567890189".to_string(),
+        ..Default::default()
+    })
+    .await;
+
+    let err = validate_intent(&chain, [1u8; 32], SOLVER, &verified_payment(Some(10000)), true)
+        .await
+        .expect_err("Payment to another IBAN must be rejected");
+
+    assert!(err.starts_with("IBAN mismatch"), "Unexpected error: {}", err);
+}
+
+#[tokio::test]
+async fn test_missing_proven_iban_is_rejected() {
+    let (_server, chain) = mock_chain(IntentFixture::default()).await;
+
+    let mut verified = verified_payment(Some(10000));
+    verified.beneficiary_iban = None;
+
+    let err = validate_intent(&chain, [1u8; 32], SOLVER, &verified, true)
+        .await
+        .expect_err("A proof without an IBAN must be rejected");
+
+    assert!(err.contains("beneficiary IBAN"), "Unexpected error: {}", err);
+}
+
+#[tokio::test]
+async fn test_different_recipient_name_is_rejected() {
+    let (_server, chain) = mock_chain(IntentFixture {
+        recipient_name: "Erika Musterfrau".to_string(),
+        ..Default::default()
+    })
+    .await;
+
+    let err = validate_intent(&chain, [1u8; 32], SOLVER, &verified_payment(Some(10000)), true)
+        .await
+        .expect_err("Payment to another person must be rejected");
+
+    assert!(err.starts_with("Recipient name mismatch"), "Unexpected error: {}", err);
+}
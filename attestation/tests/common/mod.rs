@@ -0,0 +1,259 @@
+//! Shared helpers for integration tests: chain RPC mocks and fixtures
+
+#![allow(dead_code)]
+
+use std::sync::{Arc, Mutex};
+
+use alloy_primitives::Address;
+use attestation_service::api::{self, AppState};
+use attestation_service::attestation::{AttestationResponse, PaymentDetails};
+use attestation_service::audit::AuditLogger;
+use attestation_service::auth::SolverAuth;
+use attestation_service::chain::ChainClient;
+use attestation_service::cluster::WitnessCluster;
+use attestation_service::config::{Config, Environment};
+use attestation_service::eip712::{AttestationDomain, SchemaVersion};
+use attestation_service::providers::ProviderRegistry;
+use attestation_service::server_policy::parse_server_policy;
+use attestation_service::signer::LocalSigner;
+use attestation_service::store::AttestationStore;
+use attestation_service::verification::{TlsnVerifier, VerifiedPayment};
+use attestation_service::witness::{WitnessKey, WitnessKeys};
+use k256::ecdsa::SigningKey;
+use wiremock::{
+    matchers::{body_string_contains, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+pub const DEPOSITOR: &str = "0x1111111111111111111111111111111111111111";
+pub const SOLVER: &str = "0x2222222222222222222222222222222222222222";
+pub const OFFRAMP_CONTRACT: &str = "0x34249F4AB741F0661A38651A08213DDe1469b60f";
+
+/// Fields of an OffRampV3.Intent to encode in a mocked getIntent response
+pub struct IntentFixture {
+    pub depositor: &'static str,
+    pub usdc_amount: u128,
+    pub currency: u8,
+    pub status: u8,
+    pub created_at: u64,
+    pub committed_at: u64,
+    pub solver: &'static str,
+    pub rtpn: u8,
+    pub selected_fiat_amount: u128,
+    pub receiving_info: String,
+    pub recipient_name: String,
+    pub transfer_id: [u8; 32],
+}
+
+impl Default for IntentFixture {
+    fn default() -> Self {
+        Self {
+            depositor: DEPOSITOR,
+            usdc_amount: 100_000_000, // 100 USDC (6 decimals)
+            currency: 0,              // EUR
+            status: 2,                // Committed
+            created_at: 1700000000,
+            committed_at: 1700000100,
+            solver: SOLVER,
+            rtpn: 0, // SEPA_INSTANT
+            selected_fiat_amount: 10000,
+            receiving_info: "DE89370400440532013000".to_string(),
+            recipient_name: "Max Mustermann".to_string(),
+            transfer_id: [0u8; 32],
+        }
+    }
+}
+
+/// ABI-encode a dynamic string tail: length word followed by padded bytes
+fn encode_string_tail(s: &str) -> String {
+    let mut out = format!("{:064x}", s.len());
+    let mut data = hex::encode(s.as_bytes());
+    while data.len() % 64 != 0 {
+        data.push('0');
+    }
+    out.push_str(&data);
+    out
+}
+
+/// Build a mock eth_call result for getIntent(bytes32), hand-encoded as
+/// `abi.encode(intent)` of the (dynamic) Intent struct
+pub fn encode_intent_response(intent: &IntentFixture) -> String {
+    // Head has 12 words; both strings live in the tail after it
+    let head_len = 12 * 32;
+    let receiving_info_tail = encode_string_tail(&intent.receiving_info);
+    let recipient_name_tail = encode_string_tail(&intent.recipient_name);
+
+    let mut result = String::from("0x");
+
+    // Offset pointer to the struct (0x20)
+    result.push_str(&format!("{:064x}", 32u64));
+
+    result.push_str(&format!(
+        "{:0>64}",
+        intent.depositor.trim_start_matches("0x")
+    ));
+    result.push_str(&format!("{:064x}", intent.usdc_amount));
+    result.push_str(&format!("{:064x}", intent.currency));
+    result.push_str(&format!("{:064x}", intent.status));
+    result.push_str(&format!("{:064x}", intent.created_at));
+    result.push_str(&format!("{:064x}", intent.committed_at));
+    result.push_str(&format!("{:0>64}", intent.solver.trim_start_matches("0x")));
+    result.push_str(&format!("{:064x}", intent.rtpn));
+    result.push_str(&format!("{:064x}", intent.selected_fiat_amount));
+    // Offsets of receivingInfo and recipientName, relative to struct start
+    result.push_str(&format!("{:064x}", head_len));
+    result.push_str(&format!(
+        "{:064x}",
+        head_len + receiving_info_tail.len() / 2
+    ));
+    result.push_str(&hex::encode(intent.transfer_id));
+
+    result.push_str(&receiving_info_tail);
+    result.push_str(&recipient_name_tail);
+
+    result
+}
+
+/// Start a mock RPC server that returns `result` for every JSON-RPC call
+pub async fn mock_rpc(result: String) -> MockServer {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": result
+        })))
+        .mount(&mock_server)
+        .await;
+
+    mock_server
+}
+
+/// Client for the OffRampV3 contract behind the RPC server at `uri`
+pub fn chain_client(uri: String) -> ChainClient {
+    ChainClient::new(uri, OFFRAMP_CONTRACT.parse::<Address>().unwrap())
+}
+
+/// Start a mock RPC server that serves `intent` for every eth_call, and a
+/// client for it
+pub async fn mock_chain(intent: IntentFixture) -> (MockServer, ChainClient) {
+    let mock_server = mock_rpc(encode_intent_response(&intent)).await;
+    let client = chain_client(mock_server.uri());
+    (mock_server, client)
+}
+
+/// Start a mock RPC server answering each eth_call by function selector
+pub async fn mock_rpc_calls(calls: &[([u8; 4], String)]) -> MockServer {
+    let mock_server = MockServer::start().await;
+
+    for (selector, result) in calls {
+        Mock::given(method("POST"))
+            .and(path("/"))
+            .and(body_string_contains(format!("0x{}", hex::encode(selector))))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": result
+            })))
+            .mount(&mock_server)
+            .await;
+    }
+
+    mock_server
+}
+
+/// ABI-encoded bool return value
+pub fn encode_bool(value: bool) -> String {
+    format!("0x{:064x}", value as u8)
+}
+
+/// A verified payment matching the default `IntentFixture` beneficiary
+pub fn verified_payment(amount_cents: Option<i64>) -> VerifiedPayment {
+    VerifiedPayment {
+        server_name: "thirdparty.qonto.com".to_string(),
+        timestamp: 1700000200,
+        transaction_id: Some("019b2249-50b2-7778-8b9e-000000000001".to_string()),
+        amount_cents,
+        currency: Some("EUR".to_string()),
+        beneficiary_iban: Some("DE89 3704 0044 0532 0130 00".to_string()),
+        beneficiary_name: Some("MUSTERMANN MAX".to_string()),
+        status: Some("completed".to_string()),
+        side: Some("debit".to_string()),
+        ..Default::default()
+    }
+}
+
+/// A v1 attestation of the `verified_payment` transaction, with placeholder
+/// signature and hashes
+pub fn attestation_response() -> AttestationResponse {
+    AttestationResponse {
+        success: true,
+        signature: format!("0x{}", "11".repeat(65)),
+        signatures: Vec::new(),
+        digest: format!("0x{}", "22".repeat(32)),
+        data_hash: format!("0x{}", "33".repeat(32)),
+        schema_version: SchemaVersion::V1,
+        v2: None,
+        payment: PaymentDetails {
+            transaction_id: Some("019b2249-50b2-7778-8b9e-000000000001".to_string()),
+            amount_cents: 10000,
+            currency: "EUR".to_string(),
+            beneficiary_iban: "DE89370400440532013000".to_string(),
+            status: "completed".to_string(),
+            side: "debit".to_string(),
+            timestamp: 1700000200,
+            server: "thirdparty.qonto.com".to_string(),
+        },
+    }
+}
+
+/// State of a development instance signing with `key`, without chain access,
+/// solver authentication or persistent storage
+pub fn app_state(key: SigningKey, cluster: WitnessCluster) -> AppState {
+    let domain = AttestationDomain::default();
+    let server_policy = parse_server_policy("thirdparty.qonto.com").unwrap();
+    let config = Config {
+        environment: Environment::Development,
+        witness_keys: WitnessKeys::new(vec![WitnessKey {
+            id: "default".to_string(),
+            signer: Box::new(LocalSigner::new(key)),
+            active_from: None,
+            retire_at: None,
+        }])
+        .unwrap(),
+        chain_id: 84532,
+        verifier_contract: [0u8; 20],
+        domain_name: domain.name.clone(),
+        domain_version: domain.version.clone(),
+        domain_name_v2: domain.name,
+        domain_version_v2: "2".to_string(),
+        verifier_contract_v2: [0u8; 20],
+        server_policy: server_policy.clone(),
+        trusted_notary_keys: vec![vec![2u8; 33]],
+        max_session_age_secs: 3600,
+        database_path: ":memory:".into(),
+    };
+    AppState {
+        config,
+        auth: SolverAuth::from_env(),
+        chain: None,
+        audit: AuditLogger::new(),
+        providers: ProviderRegistry::from_policy(&server_policy).unwrap(),
+        verifier: Box::new(TlsnVerifier),
+        store: AttestationStore::in_memory().unwrap(),
+        cluster,
+        self_check: Mutex::new(None),
+    }
+}
+
+/// Serve `state` behind the service's routes on a local port, returning the
+/// base URL
+pub async fn serve(state: AppState) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let app = api::router(Arc::new(state));
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    url
+}
@@ -0,0 +1,53 @@
+//! E2E tests for binding the proven payment currency to the on-chain intent
+//!
+//! Amounts are only comparable in the same currency: a GBP payment must never
+//! satisfy a EUR intent, whatever the amounts.
+
+mod common;
+
+use attestation_service::chain::validate_intent;
+use common::{mock_chain, verified_payment, IntentFixture, SOLVER};
+
+#[tokio::test]
+async fn test_matching_currency_is_accepted() {
+    let (_server, chain) = mock_chain(IntentFixture {
+        currency: 1, // GBP
+        ..Default::default()
+    })
+    .await;
+
+    let mut verified = verified_payment(Some(10000));
+    verified.currency = Some("gbp".to_string());
+
+    let result = validate_intent(&chain, [1u8; 32], SOLVER, &verified, true).await;
+
+    assert!(result.is_ok(), "Expected acceptance, got {:?}", result);
+}
+
+#[tokio::test]
+async fn test_gbp_payment_for_eur_intent_is_rejected() {
+    let (_server, chain) = mock_chain(IntentFixture::default()).await;
+
+    let mut verified = verified_payment(Some(20000));
+    verified.currency = Some("GBP".to_string());
+
+    let err = validate_intent(&chain, [1u8; 32], SOLVER, &verified, true)
+        .await
+        .expect_err("A GBP payment must not satisfy a EUR intent");
+
+    assert!(err.starts_with("Currency mismatch"), "Unexpected error: {}", err);
+}
+
+#[tokio::test]
+async fn test_missing_proven_currency_is_rejected() {
+    let (_server, chain) = mock_chain(IntentFixture::default()).await;
+
+    let mut verified = verified_payment(Some(10000));
+    verified.currency = None;
+
+    let err = validate_intent(&chain, [1u8; 32], SOLVER, &verified, true)
+        .await
+        .expect_err("A proof without a currency must be rejected");
+
+    assert!(err.contains("currency"), "Unexpected error: {}", err);
+}
@@ -0,0 +1,83 @@
+//! E2E tests for simulating fulfillIntentWithProof before returning an
+//! attestation
+//!
+//! A signature whose fulfillment would revert is withheld and the decoded
+//! revert reason is reported instead. A simulation that can't run (RPC
+//! failure) doesn't block the attestation.
+
+mod common;
+
+use alloy_primitives::keccak256;
+use attestation_service::chain::{check_fulfillment, ChainClient};
+use attestation_service::error::AttestationError;
+use common::{attestation_response, chain_client, SOLVER};
+use wiremock::{
+    matchers::{body_string_contains, method},
+    Mock, MockServer, ResponseTemplate,
+};
+
+/// Mock RPC answering every eth_call sent from the solver with `body`
+async fn mock_simulation(body: serde_json::Value) -> (MockServer, ChainClient) {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(body_string_contains(SOLVER))
+        .respond_with(ResponseTemplate::new(200).set_body_json(body))
+        .mount(&mock_server)
+        .await;
+
+    let chain = chain_client(mock_server.uri()).with_simulation();
+    (mock_server, chain)
+}
+
+#[tokio::test]
+async fn test_successful_simulation_passes() {
+    let (_server, chain) =
+        mock_simulation(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x" })).await;
+
+    let result = check_fulfillment(&chain, SOLVER, [1u8; 32], &attestation_response()).await;
+
+    assert!(result.is_ok(), "Expected acceptance, got {:?}", result);
+}
+
+#[tokio::test]
+async fn test_revert_reason_is_reported() {
+    let selector = &keccak256("NotAuthorizedWitness()".as_bytes())[..4];
+    let (_server, chain) = mock_simulation(serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "error": {
+            "code": 3,
+            "message": "execution reverted",
+            "data": format!("0x{}", hex::encode(selector))
+        }
+    }))
+    .await;
+
+    let err = check_fulfillment(&chain, SOLVER, [1u8; 32], &attestation_response())
+        .await
+        .expect_err("A reverting fulfillment must be rejected");
+
+    match err {
+        AttestationError::FulfillmentWouldRevert(reason) => {
+            assert_eq!(reason, "NotAuthorizedWitness")
+        }
+        other => panic!("Unexpected error: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_rpc_failure_does_not_block_attestation() {
+    let (_server, chain) = mock_simulation(serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "error": { "code": -32005, "message": "rate limited" }
+    }))
+    .await;
+
+    assert!(
+        check_fulfillment(&chain, SOLVER, [1u8; 32], &attestation_response())
+            .await
+            .is_ok()
+    );
+}
@@ -0,0 +1,103 @@
+//! E2E tests for decoding the full OffRampV3 Intent struct
+//!
+//! `ChainClient::get_intent` decodes the `getIntent` return data with the
+//! sol! ABI definition, including the dynamic string fields, and reports
+//! malformed data as an error.
+
+mod common;
+
+use alloy_primitives::B256;
+use attestation_service::chain::{Currency, Rtpn};
+use common::{chain_client, encode_intent_response, mock_chain, mock_rpc, IntentFixture};
+
+#[tokio::test]
+async fn test_decodes_all_intent_fields() {
+    let (_server, chain) = mock_chain(IntentFixture {
+        currency: 1, // GBP
+        rtpn: 2,     // FPS
+        created_at: 1700000000,
+        committed_at: 1700000300,
+        receiving_info: "GB29NWBK60161331926819".to_string(),
+        recipient_name: "A recipient name long enough to span more than one ABI word".to_string(),
+        transfer_id: [0xab; 32],
+        ..Default::default()
+    })
+    .await;
+
+    let intent = chain
+        .get_intent([1u8; 32])
+        .await
+        .expect("getIntent failed")
+        .expect("Intent should exist");
+
+    assert_eq!(intent.currency, Currency::Gbp);
+    assert_eq!(intent.selected_rtpn, Rtpn::Fps);
+    assert_eq!(intent.created_at, 1700000000);
+    assert_eq!(intent.committed_at, 1700000300);
+    assert_eq!(intent.receiving_info, "GB29NWBK60161331926819");
+    assert_eq!(
+        intent.recipient_name,
+        "A recipient name long enough to span more than one ABI word"
+    );
+    assert_eq!(intent.transfer_id, B256::from([0xab; 32]));
+}
+
+#[tokio::test]
+async fn test_nonexistent_intent_returns_none() {
+    let (_server, chain) = mock_chain(IntentFixture {
+        depositor: "0x0000000000000000000000000000000000000000",
+        solver: "0x0000000000000000000000000000000000000000",
+        usdc_amount: 0,
+        status: 0,
+        created_at: 0,
+        committed_at: 0,
+        selected_fiat_amount: 0,
+        receiving_info: String::new(),
+        recipient_name: String::new(),
+        ..Default::default()
+    })
+    .await;
+
+    let intent = chain
+        .get_intent([1u8; 32])
+        .await
+        .expect("getIntent failed");
+
+    assert!(intent.is_none());
+}
+
+#[tokio::test]
+async fn test_truncated_return_data_is_an_error() {
+    // Only the head of the struct, without the string tails
+    let full = encode_intent_response(&IntentFixture::default());
+    let truncated = full[..2 + 64 * 10].to_string();
+
+    let mock_server = mock_rpc(truncated).await;
+
+    let result = chain_client(mock_server.uri()).get_intent([1u8; 32]).await;
+
+    assert!(result.is_err(), "Expected decode error, got {:?}", result);
+}
+
+#[tokio::test]
+async fn test_empty_return_data_is_an_error() {
+    // e.g. OFFRAMP_CONTRACT points at an address without code
+    let mock_server = mock_rpc("0x".to_string()).await;
+
+    let result = chain_client(mock_server.uri()).get_intent([1u8; 32]).await;
+
+    assert!(result.is_err(), "Expected decode error, got {:?}", result);
+}
+
+#[tokio::test]
+async fn test_out_of_range_enum_is_an_error() {
+    let (_server, chain) = mock_chain(IntentFixture {
+        currency: 7,
+        ..Default::default()
+    })
+    .await;
+
+    let result = chain.get_intent([1u8; 32]).await;
+
+    assert!(result.is_err(), "Expected invalid currency error, got {:?}", result);
+}
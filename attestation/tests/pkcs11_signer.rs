@@ -0,0 +1,103 @@
+//! PKCS#11 witness signer against SoftHSM
+//!
+//! Needs the `pkcs11` feature and an initialized SoftHSM token:
+//!
+//! ```sh
+//! softhsm2-util --init-token --free --label freeflo-test --pin 1234 --so-pin 1234
+//! SOFTHSM2_MODULE=/usr/lib/softhsm/libsofthsm2.so \
+//!     cargo test --features pkcs11 --test pkcs11_signer -- --ignored
+//! ```
+
+#![cfg(feature = "pkcs11")]
+
+use alloy_primitives::keccak256;
+use attestation_service::signer::{address_of, Pkcs11Signer, WitnessSigner};
+use cryptoki::context::{CInitializeArgs, Pkcs11};
+use cryptoki::mechanism::Mechanism;
+use cryptoki::object::Attribute;
+use cryptoki::session::UserType;
+use cryptoki::types::AuthPin;
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+
+const TOKEN_LABEL: &str = "freeflo-test";
+const PIN: &str = "1234";
+
+/// DER-encoded OID of secp256k1 (1.3.132.0.10)
+const SECP256K1_OID: [u8; 7] = [0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x0a];
+
+fn module() -> String {
+    std::env::var("SOFTHSM2_MODULE")
+        .unwrap_or_else(|_| "/usr/lib/softhsm/libsofthsm2.so".to_string())
+}
+
+/// Generate a secp256k1 key pair labelled `label` on the test token
+fn generate_key_pair(label: &str) {
+    let context = Pkcs11::new(module()).unwrap();
+    context.initialize(CInitializeArgs::OsThreads).unwrap();
+
+    let slot = context
+        .get_slots_with_token()
+        .unwrap()
+        .into_iter()
+        .find(|slot| context.get_token_info(*slot).unwrap().label().trim() == TOKEN_LABEL)
+        .expect("SoftHSM test token not initialized");
+
+    let session = context.open_rw_session(slot).unwrap();
+    session
+        .login(UserType::User, Some(&AuthPin::new(PIN.to_string())))
+        .unwrap();
+
+    let label = label.as_bytes().to_vec();
+    session
+        .generate_key_pair(
+            &Mechanism::EccKeyPairGen,
+            &[
+                Attribute::Token(true),
+                Attribute::Verify(true),
+                Attribute::EcParams(SECP256K1_OID.to_vec()),
+                Attribute::Label(label.clone()),
+            ],
+            &[
+                Attribute::Token(true),
+                Attribute::Private(true),
+                Attribute::Sensitive(true),
+                Attribute::Sign(true),
+                Attribute::Label(label),
+            ],
+        )
+        .unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs SoftHSM, see module docs"]
+async fn test_softhsm_signature_recovers_to_witness() {
+    let label = format!("witness-{}", std::process::id());
+    generate_key_pair(&label);
+
+    let signer = Pkcs11Signer::new(&module(), TOKEN_LABEL, &label, PIN).unwrap();
+    assert_eq!(signer.backend(), "pkcs11");
+
+    // Sign enough messages to see both recovery ids and high-s signatures
+    for i in 0..16u8 {
+        let message = [i; 66];
+        let signature = signer.sign(&message).await.unwrap();
+
+        let s = Signature::from_slice(&signature[..64]).unwrap();
+        assert!(s.normalize_s().is_none(), "s must be in the lower half");
+
+        let recovered = VerifyingKey::recover_from_prehash(
+            &keccak256(message)[..],
+            &s,
+            RecoveryId::from_byte(signature[64] - 27).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(address_of(&recovered), signer.address());
+    }
+}
+
+#[test]
+#[ignore = "needs SoftHSM, see module docs"]
+fn test_missing_key_is_rejected() {
+    assert!(Pkcs11Signer::new(&module(), TOKEN_LABEL, "no-such-key", PIN).is_err());
+    assert!(Pkcs11Signer::new(&module(), "no-such-token", "witness", PIN).is_err());
+}
@@ -0,0 +1,100 @@
+//! E2E tests for checking the proven payment amount against the intent
+//!
+//! `validate_intent` receives the amount extracted from the verified
+//! TLSNotary transcript, so a solver cannot declare one amount in the request
+//! and prove a smaller payment.
+
+mod common;
+
+use attestation_service::chain::validate_intent;
+use common::{mock_chain, verified_payment, IntentFixture, SOLVER};
+
+/// An intent committed to `committed_fiat_cents`
+fn committed(committed_fiat_cents: u128) -> IntentFixture {
+    IntentFixture {
+        selected_fiat_amount: committed_fiat_cents,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_proven_amount_equal_to_committed_is_accepted() {
+    let (_server, chain) = mock_chain(committed(10000)).await;
+
+    let result = validate_intent(&chain, [1u8; 32], SOLVER, &verified_payment(Some(10000)), true).await;
+
+    assert!(result.is_ok(), "Expected acceptance, got {:?}", result);
+}
+
+#[tokio::test]
+async fn test_proven_amount_higher_than_committed_is_accepted() {
+    let (_server, chain) = mock_chain(committed(10000)).await;
+
+    let result = validate_intent(&chain, [1u8; 32], SOLVER, &verified_payment(Some(10500)), true).await;
+
+    assert!(result.is_ok(), "Expected acceptance, got {:?}", result);
+}
+
+#[tokio::test]
+async fn test_proven_amount_lower_than_committed_is_rejected() {
+    // The solver may declare 10000 in the request, but only 9500 was proven
+    let (_server, chain) = mock_chain(committed(10000)).await;
+
+    let err = validate_intent(&chain, [1u8; 32], SOLVER, &verified_payment(Some(9500)), true)
+        .await
+        .expect_err("Underpayment must be rejected");
+
+    assert_eq!(
+        err,
+        "Amount mismatch: proof shows 9500 cents paid, but solver committed to 10000 cents on-chain"
+    );
+}
+
+#[tokio::test]
+async fn test_missing_proven_amount_is_rejected() {
+    let (_server, chain) = mock_chain(committed(10000)).await;
+
+    let err = validate_intent(&chain, [1u8; 32], SOLVER, &verified_payment(None), true)
+        .await
+        .expect_err("A proof without an amount must be rejected");
+
+    assert!(err.contains("does not contain a payment amount"), "Unexpected error: {}", err);
+}
+
+#[tokio::test]
+async fn test_wrong_solver_is_rejected() {
+    let (_server, chain) = mock_chain(committed(10000)).await;
+
+    let err = validate_intent(
+        &chain,
+        [1u8; 32],
+        "0x3333333333333333333333333333333333333333",
+        &verified_payment(Some(10000)),
+        true,
+    )
+    .await
+    .expect_err("A different solver must be rejected");
+
+    assert!(err.starts_with("Solver mismatch"), "Unexpected error: {}", err);
+}
+
+#[tokio::test]
+async fn test_zero_committed_amount_is_rejected_in_strict_mode() {
+    let (_server, chain) = mock_chain(committed(0)).await;
+
+    let err = validate_intent(&chain, [1u8; 32], SOLVER, &verified_payment(Some(10000)), true)
+        .await
+        .expect_err("Strict mode must not skip the amount check");
+
+    assert!(err.contains("no committed fiat amount"), "Unexpected error: {}", err);
+}
+
+#[tokio::test]
+async fn test_zero_committed_amount_is_skipped_in_lenient_mode() {
+    let (_server, chain) = mock_chain(committed(0)).await;
+
+    let result =
+        validate_intent(&chain, [1u8; 32], SOLVER, &verified_payment(Some(10000)), false).await;
+
+    assert!(result.is_ok(), "Expected acceptance, got {:?}", result);
+}
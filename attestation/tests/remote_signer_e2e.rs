@@ -0,0 +1,111 @@
+//! E2E tests for the web3signer-style remote witness signer
+//!
+//! The mock signer hashes the posted data with keccak256 and signs it, as
+//! web3signer's `eth1` API does. The service only accepts a signature that
+//! recovers to the configured key's address.
+
+use alloy_primitives::keccak256;
+use attestation_service::eip712::{
+    attestation_digest, sign_attestation, AttestationData, AttestationDomain,
+};
+use attestation_service::signer::{address_of, RemoteSigner, WitnessSigner};
+use k256::ecdsa::{RecoveryId, Signature, SigningKey, VerifyingKey};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, Request, ResponseTemplate,
+};
+
+fn public_key(key: &SigningKey) -> String {
+    format!(
+        "0x{}",
+        hex::encode(&key.verifying_key().to_encoded_point(false).as_bytes()[1..])
+    )
+}
+
+/// Mock web3signer holding `key`, registered under `identifier`
+async fn mock_web3signer(key: SigningKey, identifier: &str) -> MockServer {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path(format!("/api/v1/eth1/sign/{}", identifier)))
+        .respond_with(move |request: &Request| {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            let data =
+                hex::decode(body["data"].as_str().unwrap().trim_start_matches("0x")).unwrap();
+
+            let (signature, recovery_id) =
+                key.sign_prehash_recoverable(&keccak256(&data)[..]).unwrap();
+            let mut sig_bytes = signature.to_bytes().to_vec();
+            sig_bytes.push(recovery_id.to_byte() + 27);
+
+            ResponseTemplate::new(200).set_body_string(format!("0x{}", hex::encode(sig_bytes)))
+        })
+        .mount(&server)
+        .await;
+
+    server
+}
+
+#[tokio::test]
+async fn test_remote_signature_recovers_to_witness() {
+    let key = SigningKey::random(&mut rand::thread_rng());
+    let identifier = public_key(&key);
+    let server = mock_web3signer(key.clone(), &identifier).await;
+
+    let signer = RemoteSigner::new(&server.uri(), &identifier).unwrap();
+    assert_eq!(signer.backend(), "remote");
+    assert_eq!(signer.address(), address_of(key.verifying_key()));
+
+    let domain = AttestationDomain::default();
+    let data = AttestationData {
+        intent_hash: [1u8; 32],
+        amount: 10000,
+        timestamp: 1703500000,
+        payment_id: "tx-123".to_string(),
+        data: b"test data".to_vec(),
+    };
+
+    let (signature, digest) = sign_attestation(&domain, &data, &signer).await.unwrap();
+    assert_eq!(digest, attestation_digest(&domain, &data));
+
+    let recovered = VerifyingKey::recover_from_prehash(
+        &digest[..],
+        &Signature::from_slice(&signature[..64]).unwrap(),
+        RecoveryId::from_byte(signature[64] - 27).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(address_of(&recovered), signer.address());
+}
+
+#[tokio::test]
+async fn test_signature_from_another_key_is_rejected() {
+    let key = SigningKey::random(&mut rand::thread_rng());
+    let identifier = public_key(&key);
+    // The remote signer answers with the wrong key
+    let server = mock_web3signer(SigningKey::random(&mut rand::thread_rng()), &identifier).await;
+
+    let signer = RemoteSigner::new(&server.uri(), &identifier).unwrap();
+
+    assert!(signer.sign(b"message").await.is_err());
+}
+
+#[tokio::test]
+async fn test_signer_error_is_reported() {
+    let key = SigningKey::random(&mut rand::thread_rng());
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("Public Key not found"))
+        .mount(&server)
+        .await;
+
+    let signer = RemoteSigner::new(&server.uri(), &public_key(&key)).unwrap();
+    let error = signer.sign(b"message").await.unwrap_err().to_string();
+
+    assert!(error.contains("Public Key not found"), "got {}", error);
+}
+
+#[test]
+fn test_invalid_public_key_is_rejected() {
+    assert!(RemoteSigner::new("http://127.0.0.1:9000", "0x1234").is_err());
+    assert!(RemoteSigner::new("http://127.0.0.1:9000", "not hex").is_err());
+}
@@ -0,0 +1,51 @@
+//! E2E tests for binding the TLS session time to the intent's commitment
+//!
+//! A proof is only accepted if its TLS session happened after the intent's
+//! on-chain `committedAt` and before `committedAt + FULFILLMENT_WINDOW`.
+
+mod common;
+
+use attestation_service::chain::{validate_intent, FULFILLMENT_WINDOW_SECS};
+use common::{mock_chain, verified_payment, IntentFixture, SOLVER};
+
+const COMMITTED_AT: u64 = 1700000100;
+
+async fn validate_at(session_time: u64) -> Result<(), String> {
+    let (_server, chain) = mock_chain(IntentFixture {
+        committed_at: COMMITTED_AT,
+        ..Default::default()
+    })
+    .await;
+
+    let mut verified = verified_payment(Some(10000));
+    verified.timestamp = session_time;
+
+    validate_intent(&chain, [1u8; 32], SOLVER, &verified, true)
+        .await
+        .map(|_| ())
+}
+
+#[tokio::test]
+async fn test_session_within_fulfillment_window_is_accepted() {
+    assert!(validate_at(COMMITTED_AT).await.is_ok());
+    assert!(validate_at(COMMITTED_AT + 600).await.is_ok());
+    assert!(validate_at(COMMITTED_AT + FULFILLMENT_WINDOW_SECS).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_session_before_commitment_is_rejected() {
+    let err = validate_at(COMMITTED_AT - 1)
+        .await
+        .expect_err("A session before the commitment must be rejected");
+
+    assert!(err.contains("predates intent commitment"), "Unexpected error: {}", err);
+}
+
+#[tokio::test]
+async fn test_session_after_fulfillment_deadline_is_rejected() {
+    let err = validate_at(COMMITTED_AT + FULFILLMENT_WINDOW_SECS + 1)
+        .await
+        .expect_err("A session after the deadline must be rejected");
+
+    assert!(err.contains("after the fulfillment deadline"), "Unexpected error: {}", err);
+}
@@ -0,0 +1,236 @@
+//! E2E tests for the self-check against the deployed PaymentVerifier
+//!
+//! The verifier must trust the witness and agree with the local EIP-712
+//! domain separator and digest. A disagreement is a failed check; a verifier
+//! that can't be reached leaves the check unavailable.
+
+mod common;
+
+use alloy_primitives::{keccak256, Address, B256};
+use attestation_service::eip712::{attestation_digest, AttestationDomain};
+use attestation_service::self_check::{run_self_check, sample_attestation, CheckStatus};
+use common::{chain_client, encode_bool, mock_rpc_calls};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const VERIFIER_CONTRACT: &str = "0x3333333333333333333333333333333333333333";
+const WITNESS: &str = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+
+fn selector(signature: &str) -> [u8; 4] {
+    keccak256(signature.as_bytes())[..4].try_into().unwrap()
+}
+
+fn encode_bytes32(value: B256) -> String {
+    format!("0x{}", hex::encode(value))
+}
+
+fn witness() -> Address {
+    WITNESS.parse().unwrap()
+}
+
+fn domain() -> AttestationDomain {
+    AttestationDomain::new(84532, verifier().into())
+}
+
+fn verifier() -> Address {
+    VERIFIER_CONTRACT.parse().unwrap()
+}
+
+/// Mock verifier answering the self-check calls, asking for one witness
+async fn mock_verifier(authorized: bool, separator: B256, digest: B256) -> MockServer {
+    mock_verifier_with_threshold(authorized, separator, digest, 1).await
+}
+
+async fn mock_verifier_with_threshold(
+    authorized: bool,
+    separator: B256,
+    digest: B256,
+    min_witnesses: u64,
+) -> MockServer {
+    mock_rpc_calls(&[
+        (
+            selector("authorizedWitnesses(address)"),
+            encode_bool(authorized),
+        ),
+        (selector("DOMAIN_SEPARATOR()"), encode_bytes32(separator)),
+        (
+            selector("getDigest((bytes32,uint256,uint256,string,bytes32))"),
+            encode_bytes32(digest),
+        ),
+        (
+            selector("minWitnesses()"),
+            format!("0x{:064x}", min_witnesses),
+        ),
+    ])
+    .await
+}
+
+fn status(report: &attestation_service::self_check::SelfCheckReport, name: &str) -> CheckStatus {
+    report
+        .checks
+        .iter()
+        .find(|c| c.name == name)
+        .unwrap()
+        .status
+}
+
+#[tokio::test]
+async fn test_matching_verifier_passes() {
+    let domain = domain();
+    let server = mock_verifier(
+        true,
+        domain.domain_separator(),
+        attestation_digest(&domain, &sample_attestation()),
+    )
+    .await;
+
+    let chain = chain_client(server.uri()).with_verifier(verifier());
+    let report = run_self_check(&chain, &[witness()], &domain, 1)
+        .await
+        .unwrap();
+
+    assert!(
+        report.passed(),
+        "Expected all checks to pass, got {:?}",
+        report
+    );
+    assert_eq!(report.mismatches().count(), 0);
+}
+
+#[tokio::test]
+async fn test_unauthorized_witness_fails() {
+    let domain = domain();
+    let server = mock_verifier(
+        false,
+        domain.domain_separator(),
+        attestation_digest(&domain, &sample_attestation()),
+    )
+    .await;
+
+    let chain = chain_client(server.uri()).with_verifier(verifier());
+    let report = run_self_check(&chain, &[witness()], &domain, 1)
+        .await
+        .unwrap();
+
+    assert!(!report.passed());
+    assert_eq!(status(&report, "witness_authorized"), CheckStatus::Failed);
+    assert_eq!(status(&report, "domain_separator"), CheckStatus::Passed);
+
+    // An untrusted witness is not a domain mismatch; rotation skips the key
+    assert!(report.configuration_matches());
+    assert_eq!(report.authorized, vec![(witness(), Some(false))]);
+    let check = report.mismatches().next().unwrap();
+    assert_eq!(check.witness.as_deref(), Some(witness().to_string().as_str()));
+}
+
+#[tokio::test]
+async fn test_every_witness_is_checked() {
+    let domain = domain();
+    let server = mock_verifier(
+        true,
+        domain.domain_separator(),
+        attestation_digest(&domain, &sample_attestation()),
+    )
+    .await;
+    let next: Address = "0x70997970c51812dc3a010c7d01b50e0d17dc79c8".parse().unwrap();
+
+    let chain = chain_client(server.uri()).with_verifier(verifier());
+    let report = run_self_check(&chain, &[witness(), next], &domain, 1)
+        .await
+        .unwrap();
+
+    assert!(report.passed());
+    assert_eq!(
+        report.authorized,
+        vec![(witness(), Some(true)), (next, Some(true))]
+    );
+    let witnesses: Vec<_> = report
+        .checks
+        .iter()
+        .filter(|c| c.name == "witness_authorized")
+        .map(|c| c.witness.clone().unwrap())
+        .collect();
+    assert_eq!(witnesses, vec![witness().to_string(), next.to_string()]);
+}
+
+#[tokio::test]
+async fn test_domain_mismatch_fails() {
+    // The verifier is deployed on another chain than CHAIN_ID says
+    let domain = domain();
+    let on_chain = AttestationDomain::new(8453, domain.verifying_contract);
+    let server = mock_verifier(
+        true,
+        on_chain.domain_separator(),
+        attestation_digest(&on_chain, &sample_attestation()),
+    )
+    .await;
+
+    let chain = chain_client(server.uri()).with_verifier(verifier());
+    let report = run_self_check(&chain, &[witness()], &domain, 1)
+        .await
+        .unwrap();
+
+    let mismatches: Vec<_> = report.mismatches().map(|c| c.name).collect();
+    assert_eq!(mismatches, vec!["domain_separator", "digest"]);
+    assert!(!report.configuration_matches());
+}
+
+#[tokio::test]
+async fn test_min_witnesses_above_collected_signatures_fails() {
+    let domain = domain();
+    let server = mock_verifier_with_threshold(
+        true,
+        domain.domain_separator(),
+        attestation_digest(&domain, &sample_attestation()),
+        2,
+    )
+    .await;
+
+    let chain = chain_client(server.uri()).with_verifier(verifier());
+    let report = run_self_check(&chain, &[witness()], &domain, 1)
+        .await
+        .unwrap();
+    let mismatches: Vec<_> = report.mismatches().map(|c| c.name).collect();
+    assert_eq!(mismatches, vec!["min_witnesses"]);
+    assert!(!report.configuration_matches());
+
+    // A cluster collecting two signatures satisfies it
+    let report = run_self_check(&chain, &[witness()], &domain, 2)
+        .await
+        .unwrap();
+    assert!(report.passed());
+}
+
+#[tokio::test]
+async fn test_unreachable_verifier_is_unavailable() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    let chain = chain_client(server.uri()).with_verifier(verifier());
+    let report = run_self_check(&chain, &[witness()], &domain(), 1)
+        .await
+        .unwrap();
+
+    assert!(!report.passed());
+    assert_eq!(report.mismatches().count(), 0);
+    assert_eq!(report.authorized, vec![(witness(), None)]);
+    assert!(report
+        .checks
+        .iter()
+        .all(|c| c.status == CheckStatus::Unavailable));
+}
+
+#[tokio::test]
+async fn test_no_verifier_skips_self_check() {
+    let chain = chain_client("http://127.0.0.1:1".to_string());
+
+    assert!(run_self_check(&chain, &[witness()], &domain(), 1)
+        .await
+        .is_none());
+}
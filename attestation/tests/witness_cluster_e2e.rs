@@ -0,0 +1,457 @@
+//! E2E tests for multi-witness co-signing
+//!
+//! The primary forwards the request to local peer witness instances, each
+//! answering with an attestation signed by its own key. The primary keeps the
+//! valid signatures of its digest and returns them ordered by witness
+//! address, or fails below the threshold.
+//!
+//! Wiremock stubs script what a peer answers. Real service instances
+//! (`AppState` behind `api::router`) run the whole path instead: the primary
+//! verifies and signs, forwards the request, and each peer checks the cluster
+//! key, verifies the request on its own, claims the payment and signs. Signing
+//! needs a presentation from a real notary, so those instances take a fixture
+//! presentation through a test `PresentationVerifier`.
+
+mod common;
+
+use alloy_primitives::{keccak256, Address, B256};
+use attestation_service::attestation::{AttestationRequest, AttestationResponse};
+use attestation_service::audit::current_timestamp;
+use attestation_service::cluster::{CosignRequest, WitnessCluster, CLUSTER_KEY_HEADER};
+use attestation_service::eip712::SchemaVersion;
+use attestation_service::error::AttestationError;
+use attestation_service::providers::ProviderRegistry;
+use attestation_service::reference::payment_reference;
+use attestation_service::signer::{address_of, recoverable_signature};
+use attestation_service::verification::{PresentationVerifier, VerifiedPayment};
+use base64::Engine;
+use common::{app_state, attestation_response, serve, SOLVER};
+use k256::ecdsa::SigningKey;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, Request, Respond, ResponseTemplate,
+};
+
+const CLUSTER_KEY: &str = "cluster-secret";
+
+fn digest() -> B256 {
+    keccak256(b"payment attestation")
+}
+
+fn random_key() -> SigningKey {
+    SigningKey::random(&mut rand::thread_rng())
+}
+
+fn address(key: &SigningKey) -> Address {
+    address_of(key.verifying_key())
+}
+
+fn sign(key: &SigningKey, digest: &B256) -> String {
+    let (signature, _) = key.sign_prehash_recoverable(&digest[..]).unwrap();
+    let signature = recoverable_signature(&signature, digest, address(key)).unwrap();
+    format!("0x{}", hex::encode(signature))
+}
+
+fn request() -> AttestationRequest {
+    AttestationRequest {
+        presentation: "cHJlc2VudGF0aW9u".to_string(),
+        intent_hash: format!("0x{}", "11".repeat(32)),
+        expected_amount_cents: 10000,
+        expected_beneficiary_iban: "DE89370400440532013000".to_string(),
+        payment_id: None,
+        schema_version: SchemaVersion::V1,
+    }
+}
+
+/// Attestation of `digest` signed by `key`
+fn attestation(key: &SigningKey, digest: &B256) -> AttestationResponse {
+    AttestationResponse {
+        signature: sign(key, digest),
+        digest: format!("{}", digest),
+        ..attestation_response()
+    }
+}
+
+/// Peer witness that has verified the payment and signs `digest`
+struct PeerWitness {
+    key: SigningKey,
+    digest: B256,
+}
+
+impl Respond for PeerWitness {
+    fn respond(&self, incoming: &Request) -> ResponseTemplate {
+        let key = incoming
+            .headers
+            .get(CLUSTER_KEY_HEADER)
+            .and_then(|v| v.to_str().ok());
+        if key != Some(CLUSTER_KEY) {
+            return ResponseTemplate::new(401)
+                .set_body_json(serde_json::json!({ "error": "Invalid X-Cluster-Key header" }));
+        }
+
+        let cosign: CosignRequest = serde_json::from_slice(&incoming.body).unwrap();
+        assert_eq!(cosign.solver_address, SOLVER);
+        assert_eq!(cosign.request.intent_hash, request().intent_hash);
+
+        ResponseTemplate::new(200).set_body_json(attestation(&self.key, &self.digest))
+    }
+}
+
+async fn peer(key: SigningKey, digest: B256) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/v1/cosign"))
+        .respond_with(PeerWitness { key, digest })
+        .mount(&server)
+        .await;
+    server
+}
+
+/// Peer witness whose own verification rejects the payment
+async fn refusing_peer(error: &str) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/v1/cosign"))
+        .respond_with(
+            ResponseTemplate::new(400)
+                .set_body_json(serde_json::json!({ "error": error, "code": 400 })),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+/// A witness instance as deployed: real `AppState` and routes, signing with
+/// `key`, without chain access. Returns its base URL.
+async fn witness_instance(key: SigningKey) -> String {
+    let cluster = WitnessCluster::new(Vec::new(), Some(CLUSTER_KEY.to_string()), 1).unwrap();
+    serve(app_state(key, cluster)).await
+}
+
+/// Presentation the fixture verifier accepts
+const FIXTURE_PRESENTATION: &[u8] = b"fixture presentation";
+
+/// Stands in for the notary: accepts `FIXTURE_PRESENTATION` as proving
+/// `payment`, so an instance goes on to validate, claim and sign it
+struct FixtureVerifier {
+    payment: VerifiedPayment,
+}
+
+impl PresentationVerifier for FixtureVerifier {
+    fn verify(
+        &self,
+        presentation: &[u8],
+        _providers: &ProviderRegistry,
+        _trusted_notary_keys: &[Vec<u8>],
+    ) -> Result<VerifiedPayment, AttestationError> {
+        if presentation != FIXTURE_PRESENTATION {
+            return Err(AttestationError::DeserializationError(
+                "not the fixture presentation".to_string(),
+            ));
+        }
+        Ok(self.payment.clone())
+    }
+}
+
+/// Settled payment for `request()`, proven a minute ago
+fn fixture_payment() -> VerifiedPayment {
+    VerifiedPayment {
+        server_name: "thirdparty.qonto.com".to_string(),
+        timestamp: current_timestamp() - 60,
+        response_body: r#"{"transactions":[{"id":"tx-1"}]}"#.to_string(),
+        transaction_id: Some("tx-1".to_string()),
+        amount_cents: Some(10000),
+        currency: Some("EUR".to_string()),
+        beneficiary_iban: Some("DE89370400440532013000".to_string()),
+        beneficiary_name: Some("Max Mustermann".to_string()),
+        status: Some("completed".to_string()),
+        side: Some("debit".to_string()),
+        reference: Some(payment_reference(&[0x11; 32])),
+        executed_at: Some(current_timestamp() - 120),
+    }
+}
+
+fn fixture_request() -> AttestationRequest {
+    AttestationRequest {
+        presentation: base64::engine::general_purpose::STANDARD.encode(FIXTURE_PRESENTATION),
+        ..request()
+    }
+}
+
+/// A witness instance as deployed, co-signed by `peers`, that takes the
+/// fixture presentation as proving `payment`. Returns its base URL.
+async fn signing_instance(
+    key: SigningKey,
+    peers: Vec<String>,
+    min_witnesses: usize,
+    payment: VerifiedPayment,
+) -> String {
+    let mut state = app_state(key, cluster_of(peers, CLUSTER_KEY, min_witnesses));
+    state.verifier = Box::new(FixtureVerifier { payment });
+    serve(state).await
+}
+
+async fn attest(service: &str, request: &AttestationRequest) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/api/v1/attest", service))
+        .json(request)
+        .send()
+        .await
+        .unwrap()
+}
+
+fn cluster(peers: &[&MockServer], key: &str, min_witnesses: usize) -> WitnessCluster {
+    cluster_of(peers.iter().map(|p| p.uri()).collect(), key, min_witnesses)
+}
+
+fn cluster_of(peers: Vec<String>, key: &str, min_witnesses: usize) -> WitnessCluster {
+    WitnessCluster::new(peers, Some(key.to_string()), min_witnesses).unwrap()
+}
+
+#[tokio::test]
+async fn test_signatures_meet_threshold_in_witness_order() {
+    let (primary, w2, w3) = (random_key(), random_key(), random_key());
+    let peer2 = peer(w2.clone(), digest()).await;
+    let peer3 = peer(w3.clone(), digest()).await;
+
+    let signatures = cluster(&[&peer2, &peer3], CLUSTER_KEY, 3)
+        .cosign(&request(), SOLVER, &attestation(&primary, &digest()))
+        .await
+        .unwrap();
+
+    let mut expected = vec![
+        (address(&primary), sign(&primary, &digest())),
+        (address(&w2), sign(&w2, &digest())),
+        (address(&w3), sign(&w3, &digest())),
+    ];
+    expected.sort_by_key(|(witness, _)| *witness);
+
+    let witnesses: Vec<_> = signatures.iter().map(|s| s.witness.clone()).collect();
+    let expected_witnesses: Vec<_> = expected.iter().map(|(w, _)| format!("{}", w)).collect();
+    assert_eq!(witnesses, expected_witnesses);
+    for (signature, (_, expected)) in signatures.iter().zip(&expected) {
+        assert_eq!(&signature.signature, expected);
+    }
+}
+
+#[tokio::test]
+async fn test_peer_signing_another_digest_is_skipped() {
+    let primary = random_key();
+    let honest = peer(random_key(), digest()).await;
+    let diverging = peer(random_key(), keccak256(b"other attestation")).await;
+
+    let signatures = cluster(&[&honest, &diverging], CLUSTER_KEY, 2)
+        .cosign(&request(), SOLVER, &attestation(&primary, &digest()))
+        .await
+        .unwrap();
+    assert_eq!(signatures.len(), 2);
+
+    let err = cluster(&[&honest, &diverging], CLUSTER_KEY, 3)
+        .cosign(&request(), SOLVER, &attestation(&primary, &digest()))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            AttestationError::InsufficientWitnesses {
+                collected: 2,
+                required: 3,
+                ..
+            }
+        ),
+        "Expected InsufficientWitnesses, got {:?}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_refusing_peer_fails_below_threshold() {
+    let primary = random_key();
+    let honest = peer(random_key(), digest()).await;
+    let refusing = refusing_peer("Payment reference does not match intent: missing").await;
+
+    let err = cluster(&[&honest, &refusing], CLUSTER_KEY, 3)
+        .cosign(&request(), SOLVER, &attestation(&primary, &digest()))
+        .await
+        .unwrap_err();
+
+    match err {
+        AttestationError::InsufficientWitnesses {
+            collected, detail, ..
+        } => {
+            assert_eq!(collected, 2);
+            assert!(
+                detail.contains("Payment reference does not match intent"),
+                "{}",
+                detail
+            );
+        }
+        other => panic!("Expected InsufficientWitnesses, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_peer_with_the_primary_key_is_not_counted_twice() {
+    let primary = random_key();
+    let copy = peer(primary.clone(), digest()).await;
+
+    let err = cluster(&[&copy], CLUSTER_KEY, 2)
+        .cosign(&request(), SOLVER, &attestation(&primary, &digest()))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        AttestationError::InsufficientWitnesses { collected: 1, .. }
+    ));
+}
+
+#[tokio::test]
+async fn test_wrong_cluster_key_is_refused() {
+    let primary = random_key();
+    let peer2 = peer(random_key(), digest()).await;
+
+    let err = cluster(&[&peer2], "not-the-cluster-key", 2)
+        .cosign(&request(), SOLVER, &attestation(&primary, &digest()))
+        .await
+        .unwrap_err();
+
+    match err {
+        AttestationError::InsufficientWitnesses { detail, .. } => {
+            assert!(detail.contains("401"), "{}", detail);
+        }
+        other => panic!("Expected InsufficientWitnesses, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_real_peer_verifies_the_forwarded_request() {
+    let primary = random_key();
+    let honest = peer(random_key(), digest()).await;
+    let instance = witness_instance(random_key()).await;
+
+    // The instance decodes the forwarded request and verifies its
+    // presentation on its own, which this placeholder presentation fails
+    let err = cluster_of(vec![honest.uri(), instance.clone()], CLUSTER_KEY, 3)
+        .cosign(&request(), SOLVER, &attestation(&primary, &digest()))
+        .await
+        .unwrap_err();
+
+    match err {
+        AttestationError::InsufficientWitnesses {
+            collected, detail, ..
+        } => {
+            assert_eq!(collected, 2);
+            assert!(detail.starts_with(&format!("{}: ", instance)), "{}", detail);
+            assert!(detail.contains("presentation"), "{}", detail);
+        }
+        other => panic!("Expected InsufficientWitnesses, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_real_peer_refuses_wrong_cluster_key() {
+    let primary = random_key();
+    let instance = witness_instance(random_key()).await;
+
+    let err = cluster_of(vec![instance], "not-the-cluster-key", 2)
+        .cosign(&request(), SOLVER, &attestation(&primary, &digest()))
+        .await
+        .unwrap_err();
+
+    match err {
+        AttestationError::InsufficientWitnesses { detail, .. } => {
+            assert!(detail.contains("401"), "{}", detail);
+            assert!(
+                detail.contains("Invalid X-Cluster-Key header"),
+                "{}",
+                detail
+            );
+        }
+        other => panic!("Expected InsufficientWitnesses, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_real_instances_each_verify_and_sign() {
+    let (primary, w2, w3) = (random_key(), random_key(), random_key());
+    let payment = fixture_payment();
+    let peer2 = signing_instance(w2.clone(), Vec::new(), 1, payment.clone()).await;
+    let peer3 = signing_instance(w3.clone(), Vec::new(), 1, payment.clone()).await;
+    let service = signing_instance(primary.clone(), vec![peer2, peer3], 3, payment).await;
+
+    let response = attest(&service, &fixture_request()).await;
+    assert_eq!(response.status(), 200);
+    let attestation: AttestationResponse = response.json().await.unwrap();
+
+    // Every instance signed the digest the primary returned
+    let digest: B256 = attestation.digest.parse().unwrap();
+    assert_eq!(attestation.signature, sign(&primary, &digest));
+
+    let mut expected = vec![
+        (address(&primary), sign(&primary, &digest)),
+        (address(&w2), sign(&w2, &digest)),
+        (address(&w3), sign(&w3, &digest)),
+    ];
+    expected.sort_by_key(|(witness, _)| *witness);
+
+    let signatures: Vec<_> = attestation
+        .signatures
+        .iter()
+        .map(|s| (s.witness.clone(), s.signature.clone()))
+        .collect();
+    let expected: Vec<_> = expected
+        .into_iter()
+        .map(|(witness, signature)| (format!("{}", witness), signature))
+        .collect();
+    assert_eq!(signatures, expected);
+}
+
+#[tokio::test]
+async fn test_real_peer_proving_another_payment_is_not_counted() {
+    let primary = random_key();
+    let payment = fixture_payment();
+    let honest = signing_instance(random_key(), Vec::new(), 1, payment.clone()).await;
+
+    // Same checks pass, but the proven body differs, and so does the digest
+    let other = VerifiedPayment {
+        response_body: r#"{"transactions":[{"id":"tx-1","note":"x"}]}"#.to_string(),
+        ..payment.clone()
+    };
+    let diverging = signing_instance(random_key(), Vec::new(), 1, other).await;
+
+    let service = signing_instance(primary, vec![honest, diverging], 3, payment).await;
+
+    let response = attest(&service, &fixture_request()).await;
+    assert_eq!(response.status(), 503);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let error = body["error"].as_str().unwrap();
+    assert!(error.contains("Only 2 of 3"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_real_peer_refuses_payment_claimed_for_another_intent() {
+    let primary = random_key();
+    let payment = fixture_payment();
+    let honest = signing_instance(random_key(), Vec::new(), 1, payment.clone()).await;
+
+    // This peer already attested the payment for intent 0x22..22
+    let mut state = app_state(random_key(), cluster_of(Vec::new(), CLUSTER_KEY, 1));
+    state.verifier = Box::new(FixtureVerifier {
+        payment: payment.clone(),
+    });
+    state
+        .store
+        .claim_payment(&payment, &[0x22; 32], SOLVER, current_timestamp())
+        .unwrap();
+    let claimed = serve(state).await;
+
+    let service = signing_instance(primary, vec![honest, claimed.clone()], 3, payment).await;
+
+    let response = attest(&service, &fixture_request()).await;
+    assert_eq!(response.status(), 503);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let error = body["error"].as_str().unwrap();
+    assert!(error.contains("Only 2 of 3"), "{}", error);
+    assert!(error.contains(&format!("{}: ", claimed)), "{}", error);
+    assert!(error.contains("already attested"), "{}", error);
+}
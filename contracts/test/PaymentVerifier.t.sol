@@ -123,6 +123,39 @@ contract PaymentVerifierTest is Test {
         assertFalse(verifier.authorizedWitnesses(newWitness));
     }
 
+    /// @dev Same vectors as attestation/src/typed_data.rs: the attestation service
+    /// must hash this attestation exactly as the verifier does
+    function test_MatchesServiceVectors() public {
+        vm.chainId(84532);
+        PaymentVerifier deployed = new PaymentVerifier(witness);
+        assertEq(address(deployed), 0x2e234DAe75C793f67A35089C9d99245E1C58470b);
+
+        PaymentVerifier.PaymentAttestation memory attestation = PaymentVerifier.PaymentAttestation({
+            intentHash: bytes32(uint256(1)),
+            amount: 10000,
+            timestamp: 1703500000,
+            paymentId: "tx-123-abc",
+            dataHash: keccak256("test data")
+        });
+
+        assertEq(
+            deployed.PAYMENT_ATTESTATION_TYPEHASH(),
+            0xe95e77676d6a81cd4b39cfc7f84fc889a78db30963ad8e89cc6ae7c19c9fa960
+        );
+        assertEq(
+            deployed.DOMAIN_SEPARATOR(),
+            0x5446b5d6fbdba6805931a1e60d955a2644bb6156941854df2d37ef48c304d6f5
+        );
+        assertEq(
+            _hashAttestation(attestation),
+            0xc9edd42c964033f73537a36a05e8e2f112f9628a3caac8163bb81700fea256fa
+        );
+        assertEq(
+            deployed.getDigest(attestation),
+            0xca05fe80a4187afb0bac15470a51ab239d752b43f9393bddeddffa2b959184b0
+        );
+    }
+
     // ============ Helper Functions ============
 
     function _hashAttestation(PaymentVerifier.PaymentAttestation memory attestation)
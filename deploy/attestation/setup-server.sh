@@ -138,6 +138,11 @@ if [ ! -f /etc/freeflo/attestation.env ]; then
 # FreeFlo Attestation Service Configuration
 # IMPORTANT: Fill in WITNESS_PRIVATE_KEY before starting the service
 
+# Environment profile - switch to production once deploy-phase2.sh has
+# configured solver auth and on-chain validation (production refuses to
+# start without them)
+ENVIRONMENT=staging
+
 # Witness signing key (REQUIRED - get from cast wallet new)
 WITNESS_PRIVATE_KEY=0x_REPLACE_WITH_YOUR_WITNESS_PRIVATE_KEY
 
@@ -147,6 +152,7 @@ VERIFIER_CONTRACT=0xd72ddbFAfFc390947CB6fE26afCA8b054abF21fe
 
 # TLS proof validation
 ALLOWED_SERVERS=thirdparty.qonto.com
+TRUSTED_NOTARY_KEYS=0x_REPLACE_WITH_YOUR_NOTARY_PUBLIC_KEY
 
 # Service configuration
 PORT=4001
@@ -157,7 +163,7 @@ ENVEOF
     chown freeflo:freeflo /etc/freeflo/attestation.env
 
     echo ""
-    echo "  ⚠️  IMPORTANT: Edit /etc/freeflo/attestation.env and set WITNESS_PRIVATE_KEY"
+    echo "  ⚠️  IMPORTANT: Edit /etc/freeflo/attestation.env and set WITNESS_PRIVATE_KEY and TRUSTED_NOTARY_KEYS"
     echo ""
 fi
 
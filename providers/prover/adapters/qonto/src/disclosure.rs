@@ -0,0 +1,275 @@
+// Field-level disclosure of the Qonto transactions response.
+// Shared by the prover, which commits to every JSON value on its own, and the
+// presentation builder, which reveals the JSON structure plus the values at
+// the configured paths. Both must split the body the same way.
+
+use std::ops::Range;
+
+use spansy::Spanned;
+use tlsn_formats::{http::Body, json::JsonValue};
+
+/// JSON paths revealed when `QONTO_REVEAL_PATHS` is not set
+pub const DEFAULT_REVEAL_PATHS: &[&str] = &[
+    "transactions[0].id",
+    "transactions[0].amount",
+    "transactions[0].amount_cents",
+    "transactions[0].currency",
+    "transactions[0].status",
+    "transactions[0].side",
+    "transactions[0].reference",
+    "transactions[0].label",
+    "transactions[0].settled_at",
+    "transactions[0].transfer.counterparty_account_number",
+];
+
+/// JSON paths to reveal, from the comma-separated `QONTO_REVEAL_PATHS`
+pub fn reveal_paths() -> Vec<String> {
+    match std::env::var("QONTO_REVEAL_PATHS") {
+        Ok(paths) if !paths.trim().is_empty() => paths
+            .split(',')
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .collect(),
+        _ => DEFAULT_REVEAL_PATHS.iter().map(|p| p.to_string()).collect(),
+    }
+}
+
+/// Look up a value by path, e.g. `transactions[0].transfer.counterparty_account_number`
+pub fn lookup<'a>(value: &'a JsonValue, path: &str) -> Option<&'a JsonValue> {
+    let mut current = value;
+
+    for segment in path.split('.') {
+        let (key, mut indices) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+
+        if !key.is_empty() {
+            current = match current {
+                JsonValue::Object(object) => object
+                    .elems
+                    .iter()
+                    .find(|kv| kv.key.span().as_str().trim_matches('"') == key)
+                    .map(|kv| &kv.value)?,
+                _ => return None,
+            };
+        }
+
+        while let Some(rest) = indices.strip_prefix('[') {
+            let (index, tail) = rest.split_once(']')?;
+            current = match current {
+                JsonValue::Array(array) => array.elems.get(index.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+            indices = tail;
+        }
+
+        if !indices.is_empty() {
+            return None;
+        }
+    }
+
+    Some(current)
+}
+
+/// Byte ranges of every primitive value in `value`, in order.
+/// String values include their quotes, so a hidden string is hidden whole.
+pub fn value_ranges(value: &JsonValue, received: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    collect_value_ranges(value, received, &mut ranges);
+    ranges.sort_by_key(|r| r.start);
+    ranges
+}
+
+fn collect_value_ranges(value: &JsonValue, received: &[u8], ranges: &mut Vec<Range<usize>>) {
+    match value {
+        JsonValue::Object(object) => {
+            for kv in &object.elems {
+                collect_value_ranges(&kv.value, received, ranges);
+            }
+        }
+        JsonValue::Array(array) => {
+            for elem in &array.elems {
+                collect_value_ranges(elem, received, ranges);
+            }
+        }
+        JsonValue::String(_) => {
+            for mut range in value.span().indices().iter_ranges() {
+                if range.start > 0 && received.get(range.start - 1) == Some(&b'"') {
+                    range.start -= 1;
+                }
+                if received.get(range.end) == Some(&b'"') {
+                    range.end += 1;
+                }
+                ranges.push(range);
+            }
+        }
+        _ => ranges.extend(value.span().indices().iter_ranges()),
+    }
+}
+
+/// Byte range of a response body in the received transcript
+pub fn body_range(body: &Body) -> Range<usize> {
+    let mut ranges = body.span().indices().iter_ranges();
+    let first = ranges.next().unwrap_or_default();
+    let end = ranges.last().map_or(first.end, |r| r.end);
+    first.start..end
+}
+
+/// Byte ranges of `body` not covered by a value: braces, brackets, commas,
+/// colons, keys and whitespace. The attestation service needs all of it to
+/// place the revealed values by path.
+pub fn structure_ranges(body: Range<usize>, values: &[Range<usize>]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = body.start;
+
+    for value in values {
+        if value.start > start {
+            ranges.push(start..value.start);
+        }
+        start = start.max(value.end);
+    }
+    if body.end > start {
+        ranges.push(start..body.end);
+    }
+
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spansy::http::parse_response;
+    use tlsn_formats::http::BodyContent;
+
+    const BODY: &str = r#"{"transactions":[{"id":"tx-1","amount_cents":100,"transfer":{"counterparty_account_number":"DE89"}},{"id":"tx-2"}],"meta":{"total":2}}"#;
+
+    /// A Qonto-shaped response and its parsed body
+    fn response() -> (Vec<u8>, Body) {
+        let received = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+            BODY.len(),
+            BODY
+        )
+        .into_bytes();
+        let body = parse_response(&received).unwrap().body.unwrap();
+        (received, body)
+    }
+
+    fn json(body: &Body) -> &JsonValue {
+        match &body.content {
+            BodyContent::Json(json) => json,
+            _ => panic!("body is not JSON"),
+        }
+    }
+
+    /// The revealed text of each range
+    fn texts<'a>(received: &'a [u8], ranges: &[Range<usize>]) -> Vec<&'a str> {
+        ranges
+            .iter()
+            .map(|r| std::str::from_utf8(&received[r.clone()]).unwrap())
+            .collect()
+    }
+
+    /// The text of the value at `path`
+    fn lookup_text(received: &[u8], body: &Body, path: &str) -> Option<String> {
+        let value = lookup(json(body), path)?;
+        Some(texts(received, &value_ranges(value, received)).concat())
+    }
+
+    #[test]
+    fn test_lookup_nested_paths_and_indexes() {
+        let (received, body) = response();
+
+        assert_eq!(
+            lookup_text(&received, &body, "transactions[0].id").as_deref(),
+            Some(r#""tx-1""#)
+        );
+        assert_eq!(
+            lookup_text(
+                &received,
+                &body,
+                "transactions[0].transfer.counterparty_account_number"
+            )
+            .as_deref(),
+            Some(r#""DE89""#)
+        );
+        assert_eq!(
+            lookup_text(&received, &body, "transactions[1].id").as_deref(),
+            Some(r#""tx-2""#)
+        );
+        assert_eq!(
+            lookup_text(&received, &body, "meta.total").as_deref(),
+            Some("2")
+        );
+    }
+
+    #[test]
+    fn test_lookup_missing_paths() {
+        let (_, body) = response();
+        let json = json(&body);
+
+        assert!(lookup(json, "transactions[2]").is_none());
+        assert!(lookup(json, "transactions[0].missing").is_none());
+        assert!(lookup(json, "transactions[0].transfer.iban").is_none());
+        // Index into an object, key into an array
+        assert!(lookup(json, "meta[0]").is_none());
+        assert!(lookup(json, "transactions.id").is_none());
+        // Malformed indexes
+        assert!(lookup(json, "transactions[x]").is_none());
+        assert!(lookup(json, "transactions[0").is_none());
+        assert!(lookup(json, "transactions[0]x").is_none());
+    }
+
+    #[test]
+    fn test_value_ranges_include_string_quotes() {
+        let (received, body) = response();
+
+        let values = value_ranges(json(&body), &received);
+        assert_eq!(
+            texts(&received, &values),
+            [r#""tx-1""#, "100", r#""DE89""#, r#""tx-2""#, "2"]
+        );
+        assert!(values.windows(2).all(|w| w[0].end <= w[1].start));
+    }
+
+    #[test]
+    fn test_body_range() {
+        let (received, body) = response();
+
+        let range = body_range(&body);
+        assert_eq!(&received[range.clone()], BODY.as_bytes());
+        assert_eq!(range.end, received.len());
+    }
+
+    #[test]
+    fn test_structure_ranges_cover_everything_but_values() {
+        let (received, body) = response();
+        let values = value_ranges(json(&body), &received);
+        let body = body_range(&body);
+
+        let structure = structure_ranges(body.clone(), &values);
+        assert_eq!(
+            texts(&received, &structure)[..3],
+            [
+                r#"{"transactions":[{"id":"#,
+                r#","amount_cents":"#,
+                r#","transfer":{"counterparty_account_number":"#
+            ]
+        );
+
+        // Structure and values tile the body exactly
+        let mut all: Vec<_> = structure.iter().chain(&values).cloned().collect();
+        all.sort_by_key(|r| r.start);
+        assert_eq!(all.first().unwrap().start, body.start);
+        assert_eq!(all.last().unwrap().end, body.end);
+        assert!(all.windows(2).all(|w| w[0].end == w[1].start));
+    }
+
+    #[test]
+    fn test_structure_ranges_skip_empty_gaps() {
+        assert_eq!(structure_ranges(0..10, &[2..4, 6..8]), [0..2, 4..6, 8..10]);
+        assert_eq!(
+            structure_ranges(0..4, &[0..2, 2..4]),
+            Vec::<Range<usize>>::new()
+        );
+        assert_eq!(structure_ranges(0..4, &[]), [0..4]);
+    }
+}
@@ -1,7 +1,10 @@
 // Qonto TLSNotary Presentation Builder - Transfer Proof
 // Creates a verifiable presentation with selective disclosure of transfer details.
-// Reveals: transaction ID, amount, status, reference, counterparty IBAN
-// Hides: Authorization header, account balances, other sensitive data
+// Reveals: the JSON structure and the values at QONTO_REVEAL_PATHS (by default
+// transaction ID, amount, currency, status, side, reference, counterparty IBAN/name)
+// Hides: Authorization header, account balances, every other JSON value
+
+mod disclosure;
 
 use hyper::header;
 
@@ -46,7 +49,8 @@ async fn create_transfer_presentation() -> Result<(), Box<dyn std::error::Error>
             // Only reveal header name, not value (credentials)
             builder.reveal_sent(&header.without_value())?;
         } else {
-            builder.reveal_sent(header)?;
+            builder.reveal_sent(&header.without_value())?;
+            builder.reveal_sent(&header.value)?;
         }
     }
 
@@ -62,33 +66,37 @@ async fn create_transfer_presentation() -> Result<(), Box<dyn std::error::Error>
     }
 
     // === JSON BODY SELECTIVE DISCLOSURE ===
-    let content = &response.body.as_ref().unwrap().content;
-
-    match content {
-        tlsn_formats::http::BodyContent::Json(_json) => {
-            // For now, reveal the full JSON body since TLSNotary requires all
-            // committed data to be covered in the proof
-            // In production, we'd use a custom committer that only commits
-            // to the fields we want to reveal
-            println!("\n📋 Revealing full JSON body for attestation");
-            builder.reveal_recv(content)?;
-        }
-        tlsn_formats::http::BodyContent::Unknown(span) => {
-            // Reveal the full body when JSON parsing fails
-            println!("  Revealing raw response body (JSON parse failed)");
-            builder.reveal_recv(span)?;
-        }
-        _ => {
-            println!("  (no body content)");
+    let body = response.body.as_ref().ok_or("response has no body")?;
+
+    let tlsn_formats::http::BodyContent::Json(json) = &body.content else {
+        return Err("response body is not JSON".into());
+    };
+
+    // The structure (braces, keys, commas) is always revealed so the
+    // attestation service can locate values by path
+    let received = secrets.transcript().received();
+    let values = disclosure::value_ranges(json, received);
+    for range in disclosure::structure_ranges(disclosure::body_range(body), &values) {
+        builder.reveal_recv(&range)?;
+    }
+
+    println!("\n📋 Revealed fields:");
+    for path in disclosure::reveal_paths() {
+        match disclosure::lookup(json, &path) {
+            Some(value) => {
+                for range in disclosure::value_ranges(value, received) {
+                    builder.reveal_recv(&range)?;
+                }
+                println!("  ✓ {}", path);
+            }
+            None => println!("  - {} (not in response)", path),
         }
     }
 
     // Fields we explicitly DO NOT reveal:
     println!("\n🔒 Redacted fields:");
     println!("  ✗ Authorization header (credentials)");
-    println!("  ✗ settled_balance (account balance)");
-    println!("  ✗ bank_account_id (internal ID)");
-    println!("  ✗ initiator_id (user ID)");
+    println!("  ✗ Every other JSON value (settled_balance, bank_account_id, initiator_id, ...)");
 
     let transcript_proof = builder.build()?;
 
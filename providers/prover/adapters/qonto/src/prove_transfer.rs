@@ -1,6 +1,11 @@
 // Qonto TLSNotary Prover - Transfer Verification
 // Proves a specific SEPA transfer was completed by querying the transactions endpoint.
 // This generates an attestation that can be verified by the attestation service.
+// Every JSON value in the response is committed on its own, so the presentation
+// can reveal individual fields (see disclosure.rs).
+
+#[allow(dead_code)] // lookup and reveal_paths are only used by the presenter
+mod disclosure;
 
 use std::env;
 
@@ -25,10 +30,10 @@ use tlsn::{
     config::{ProtocolConfig, ProtocolConfigValidator},
     connection::{ConnectionInfo, HandshakeData, ServerName, TranscriptLength},
     prover::{state::Committed, ProveConfig, Prover, ProverConfig, ProverOutput, TlsConfig},
-    transcript::{ContentType, TranscriptCommitConfig},
+    transcript::{ContentType, TranscriptCommitConfig, TranscriptCommitConfigBuilder},
     verifier::{Verifier, VerifierConfig, VerifierOutput, VerifyConfig},
 };
-use tlsn_formats::http::{DefaultHttpCommitter, HttpCommit, HttpTranscript};
+use tlsn_formats::http::{BodyContent, HttpTranscript};
 
 // Qonto API configuration
 const QONTO_HOST: &str = "thirdparty.qonto.com";
@@ -226,7 +231,7 @@ async fn prover<S: AsyncWrite + AsyncRead + Send + Sync + Unpin + 'static>(
 
     // Commit to the transcript
     let mut builder = TranscriptCommitConfig::builder(prover.transcript());
-    DefaultHttpCommitter::default().commit_transcript(&mut builder, &transcript)?;
+    commit_transcript(&mut builder, &transcript, prover.transcript().received())?;
     let transcript_commit = builder.build()?;
 
     // Build attestation request
@@ -255,6 +260,57 @@ async fn prover<S: AsyncWrite + AsyncRead + Send + Sync + Unpin + 'static>(
     Ok(())
 }
 
+/// Commit to the transcript at the granularity the presentation reveals it:
+/// request and response heads piece by piece, the JSON body's structure, and
+/// each JSON value on its own.
+fn commit_transcript(
+    builder: &mut TranscriptCommitConfigBuilder,
+    transcript: &HttpTranscript,
+    received: &[u8],
+) -> Result<(), Box<dyn std::error::Error>> {
+    for request in &transcript.requests {
+        builder.commit_sent(&request.without_data())?;
+        builder.commit_sent(&request.request.target)?;
+
+        // Header names and values separately, so the Authorization value
+        // can stay hidden
+        for header in &request.headers {
+            builder.commit_sent(&header.without_value())?;
+            builder.commit_sent(&header.value)?;
+        }
+    }
+
+    for response in &transcript.responses {
+        builder.commit_recv(&response.without_data())?;
+
+        for header in &response.headers {
+            builder.commit_recv(header)?;
+        }
+
+        let Some(body) = &response.body else {
+            continue;
+        };
+
+        match &body.content {
+            BodyContent::Json(json) => {
+                let values = disclosure::value_ranges(json, received);
+
+                for range in disclosure::structure_ranges(disclosure::body_range(body), &values) {
+                    builder.commit_recv(&range)?;
+                }
+                for range in values {
+                    builder.commit_recv(&range)?;
+                }
+            }
+            _ => {
+                builder.commit_recv(body)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 async fn notarize(
     mut prover: Prover<Committed>,
     config: &RequestConfig,
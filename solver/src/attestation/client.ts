@@ -96,6 +96,25 @@ export class AttestationClient {
     };
   }
 
+  /**
+   * Get the payment reference the fiat transfer for an intent must carry.
+   * The attestation service rejects proofs of transfers without it.
+   */
+  async getPaymentReference(intentHash: string): Promise<string> {
+    const response = await this.fetch(`/api/v1/reference/${intentHash}`, {
+      method: "GET",
+    });
+
+    // eslint-disable-next-line @typescript-eslint/no-explicit-any
+    const data: any = await response.json();
+
+    if (!response.ok) {
+      throw new Error(`Payment reference request failed: ${data.error ?? response.status}`);
+    }
+
+    return data.reference as string;
+  }
+
   /**
    * Request an attestation for a TLSNotary presentation
    */
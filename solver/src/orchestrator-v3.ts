@@ -472,6 +472,8 @@ export class SolverOrchestratorV3 {
       } else {
         // Step 1: Execute fiat transfer
         log.info({ intentId }, "Step 1/4: Executing fiat transfer");
+        // The attestation service only attests a transfer carrying this reference
+        const reference = await this.attestation.getPaymentReference(intentId);
         const result = await provider.executeTransfer({
           intentId,
           usdcAmount: BigInt(usdcAmount),
@@ -480,6 +482,7 @@ export class SolverOrchestratorV3 {
           rtpn: selectedRtpn as RTPN,
           receivingInfo,
           recipientName,
+          reference,
         });
 
         if (!result.success) {
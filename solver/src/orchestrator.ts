@@ -442,6 +442,8 @@ export class SolverOrchestrator {
         rtpn: selectedRtpn as RTPN,
         receivingInfo,
         recipientName,
+        // No attestation service checks the reference in this flow
+        reference: `OFFRAMP-${intentId.substring(0, 8)}`,
       });
 
       const transferDuration = (Date.now() - transferStartTime) / 1000;
@@ -159,6 +159,7 @@ export class QontoProvider extends BaseProvider {
       rtpn,
       receivingInfo,
       recipientName,
+      reference,
     } = request;
 
     log.info(
@@ -204,7 +205,6 @@ export class QontoProvider extends BaseProvider {
 
       // Step 2: Create the transfer
       const amountEur = (Number(fiatAmount) / 100).toFixed(2);
-      const reference = `OFFRAMP-${intentId.substring(0, 8)}`;
 
       // Always use beneficiary object with name/IBAN to match VoP token
       // (beneficiary_id would cause VoP signature mismatch)
@@ -99,6 +99,7 @@ export interface TransferRequest {
   rtpn: RTPN;
   receivingInfo: string;    // IBAN, PIX key, etc.
   recipientName: string;
+  reference: string;        // Transfer reference; the zkTLS flow must use the intent's payment reference
 }
 
 export interface TransferResult {